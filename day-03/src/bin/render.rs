@@ -0,0 +1,13 @@
+use day_03::render::{render_ansi, render_html};
+
+#[tracing::instrument]
+fn main() -> anyhow::Result<()> {
+    tracing_subscriber::fmt::init();
+
+    let file = include_str!("../../input1.txt");
+    match std::env::args().any(|arg| arg == "--html") {
+        true => println!("{}", render_html(file)?),
+        false => println!("{}", render_ansi(file)?),
+    }
+    Ok(())
+}
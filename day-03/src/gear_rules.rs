@@ -0,0 +1,190 @@
+use std::ops::RangeInclusive;
+
+use anyhow::anyhow;
+
+use crate::part1::{SchematicPart, SchematicSymbol};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    Product,
+    Sum,
+    Max,
+}
+
+impl Aggregate {
+    // a symbol with no parts around it adds nothing, whichever way the parts are combined
+    pub fn apply(&self, values: impl Iterator<Item = u64>) -> anyhow::Result<u64> {
+        let mut values = values.peekable();
+        if values.peek().is_none() {
+            return Ok(0);
+        }
+        match self {
+            Aggregate::Product => values.try_fold(1u64, |acc, v| acc.checked_mul(v)),
+            Aggregate::Sum => values.try_fold(0u64, |acc, v| acc.checked_add(v)),
+            Aggregate::Max => values.max(),
+        }
+        .ok_or_else(|| anyhow!("{:?} of the parts does not fit in a u64", self))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GearRule {
+    pub symbols: Vec<char>,
+    pub part_count: RangeInclusive<usize>,
+    pub aggregate: Aggregate,
+}
+
+#[derive(Debug)]
+pub struct GearMatch<'a> {
+    pub symbol: &'a SchematicSymbol,
+    pub parts: Vec<&'a SchematicPart>,
+    pub value: u64,
+}
+
+impl GearRule {
+    pub fn new(
+        symbols: impl IntoIterator<Item = char>,
+        part_count: RangeInclusive<usize>,
+        aggregate: Aggregate,
+    ) -> Self {
+        GearRule {
+            symbols: symbols.into_iter().collect(),
+            part_count,
+            aggregate,
+        }
+    }
+
+    pub fn matches<'a>(
+        &self,
+        symbols: &'a [SchematicSymbol],
+        parts: &'a [SchematicPart],
+    ) -> anyhow::Result<Vec<GearMatch<'a>>> {
+        symbols
+            .iter()
+            .filter(|s| self.symbols.contains(&s.char))
+            .filter_map(|symbol| {
+                let adjacent_parts = parts
+                    .iter()
+                    .filter(|p| p.is_adjacent_to(symbol))
+                    .collect::<Vec<_>>();
+
+                match self.part_count.contains(&adjacent_parts.len()) {
+                    false => None,
+                    true => Some(
+                        self.aggregate
+                            .apply(adjacent_parts.iter().map(|p| p.id as u64))
+                            .map(|value| GearMatch {
+                                symbol,
+                                value,
+                                parts: adjacent_parts,
+                            }),
+                    ),
+                }
+            })
+            .collect()
+    }
+
+    pub fn total(
+        &self,
+        symbols: &[SchematicSymbol],
+        parts: &[SchematicPart],
+    ) -> anyhow::Result<u64> {
+        self.matches(symbols, parts)?
+            .iter()
+            .try_fold(0u64, |acc, m| acc.checked_add(m.value))
+            .ok_or_else(|| anyhow!("Total of the gear values does not fit in a u64"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::assert_equal;
+
+    use super::*;
+    use crate::part1::{collect_schematic_parts, collect_schematic_symbols};
+
+    const INPUT: &str = "467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..";
+
+    #[test]
+    fn test_gear_rule() -> anyhow::Result<()> {
+        let symbols = collect_schematic_symbols(INPUT);
+        let parts = collect_schematic_parts(INPUT);
+        let rule = GearRule::new(['*'], 2..=2, Aggregate::Product);
+
+        let matches = rule.matches(&symbols, &parts)?;
+        assert_equal(
+            vec![(1, 3), (8, 5)],
+            matches.iter().map(|m| (m.symbol.row_num, m.symbol.pos)),
+        );
+        assert_equal(
+            vec![vec![467, 35], vec![755, 598]],
+            matches
+                .iter()
+                .map(|m| m.parts.iter().map(|p| p.id).collect::<Vec<_>>()),
+        );
+        assert_eq!(467835, rule.total(&symbols, &parts)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_part_count_range() -> anyhow::Result<()> {
+        let symbols = collect_schematic_symbols(INPUT);
+        let parts = collect_schematic_parts(INPUT);
+
+        let rule = GearRule::new(['*'], 1..=1, Aggregate::Sum);
+        assert_eq!(617, rule.total(&symbols, &parts)?);
+
+        let rule = GearRule::new(['*'], 0..=2, Aggregate::Sum);
+        assert_eq!(467 + 35 + 617 + 755 + 598, rule.total(&symbols, &parts)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_symbol_set_and_aggregate() -> anyhow::Result<()> {
+        let symbols = collect_schematic_symbols(INPUT);
+        let parts = collect_schematic_parts(INPUT);
+
+        let rule = GearRule::new(['#', '+', '$'], 1..=usize::MAX, Aggregate::Max);
+        let matches = rule.matches(&symbols, &parts)?;
+        assert_equal(vec!['#', '+', '$'], matches.iter().map(|m| m.symbol.char));
+        assert_equal(vec![633, 592, 664], matches.iter().map(|m| m.value));
+        Ok(())
+    }
+
+    #[test]
+    fn test_aggregate_overflow() -> anyhow::Result<()> {
+        let input = "999.999
+...*...
+999.999";
+        let symbols = collect_schematic_symbols(input);
+        let parts = collect_schematic_parts(input);
+
+        let rule = GearRule::new(['*'], 1..=4, Aggregate::Product);
+        assert_eq!(996_005_996_001, rule.total(&symbols, &parts)?);
+
+        assert!(Aggregate::Product.apply([u64::MAX, 2].into_iter()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_empty_aggregate() -> anyhow::Result<()> {
+        for aggregate in [Aggregate::Product, Aggregate::Sum, Aggregate::Max] {
+            assert_eq!(0, aggregate.apply(std::iter::empty())?);
+        }
+
+        let symbols = collect_schematic_symbols(INPUT);
+        let parts = collect_schematic_parts(INPUT);
+        let rule = GearRule::new(['*', '#'], 0..=2, Aggregate::Product);
+        assert_eq!(467835 + 617 + 633, rule.total(&symbols, &parts)?);
+        Ok(())
+    }
+}
@@ -1,9 +1,11 @@
-use regex::Regex;
+use anyhow::anyhow;
 
 #[tracing::instrument]
 pub fn solve(input: &str) -> anyhow::Result<String> {
-    let symbols = collect_schematic_symbols(input);
-    let schematic_parts = collect_schematic_parts(input);
+    let Schematic {
+        parts: schematic_parts,
+        symbols,
+    } = scan_schematic(input)?;
 
     let sum: u32 = schematic_parts
         .iter()
@@ -21,6 +23,7 @@ pub struct SchematicSymbol {
     pub pos: u16,
 }
 
+#[derive(Debug, Clone)]
 pub struct SchematicPart {
     pub id: u16,
     pub row_num: u16,
@@ -30,54 +33,107 @@ pub struct SchematicPart {
 
 impl SchematicPart {
     pub fn is_engine_part(&self, symbol_coords: &[SchematicSymbol]) -> bool {
-        symbol_coords.iter().any(|symbol| {
-            if self.row_num.abs_diff(symbol.row_num) > 1 {
-                return false;
+        symbol_coords
+            .iter()
+            .any(|symbol| self.is_adjacent_to(symbol))
+    }
+
+    pub fn is_adjacent_to(&self, symbol: &SchematicSymbol) -> bool {
+        if self.row_num.abs_diff(symbol.row_num) > 1 {
+            return false;
+        }
+        let start_pos = match self.start_pos == 0 {
+            true => self.start_pos,
+            false => self.start_pos - 1,
+        };
+        (start_pos..=self.end_pos).contains(&symbol.pos)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Schematic {
+    pub parts: Vec<SchematicPart>,
+    pub symbols: Vec<SchematicSymbol>,
+}
+
+pub fn scan_schematic(input: &str) -> anyhow::Result<Schematic> {
+    let mut schematic = Schematic::default();
+    let mut current_part: Option<SchematicPart> = None;
+    let mut row_num: u16 = 0;
+    let mut pos: u16 = 0;
+
+    for c in input.chars() {
+        // positions are u16, so a row can not run past u16::MAX characters
+        let next_pos = pos.checked_add(1);
+        let row_too_long = || anyhow!("Row {} is longer than {} characters", row_num, u16::MAX);
+
+        if !c.is_ascii_digit() {
+            if let Some(part) = current_part.take() {
+                schematic.parts.push(part);
             }
-            let start_pos = match self.start_pos == 0 {
-                true => self.start_pos,
-                false => self.start_pos - 1,
-            };
-            (start_pos..=self.end_pos).contains(&symbol.pos)
-        })
+        }
+
+        match c {
+            '\n' => {
+                row_num = row_num
+                    .checked_add(1)
+                    .ok_or_else(|| anyhow!("More than {} rows", u16::MAX))?;
+                pos = 0;
+                continue;
+            }
+            // CRLF line endings, the '\n' that follows takes care of the row
+            '\r' => continue,
+            '0'..='9' => {
+                let digit = c as u16 - '0' as u16;
+                match current_part.as_mut() {
+                    Some(part) => {
+                        part.id = part
+                            .id
+                            .checked_mul(10)
+                            .and_then(|id| id.checked_add(digit))
+                            .ok_or_else(|| {
+                                anyhow!("Part number too large at row {}, pos {}", row_num, pos)
+                            })?;
+                        part.end_pos = next_pos.ok_or_else(row_too_long)?;
+                    }
+                    None => {
+                        current_part = Some(SchematicPart {
+                            id: digit,
+                            row_num,
+                            start_pos: pos,
+                            end_pos: next_pos.ok_or_else(row_too_long)?,
+                        })
+                    }
+                }
+            }
+            '.' => {}
+            c if c.is_alphanumeric() || c.is_whitespace() => {}
+            c => schematic.symbols.push(SchematicSymbol {
+                char: c,
+                row_num,
+                pos,
+            }),
+        }
+        pos = next_pos.ok_or_else(row_too_long)?;
     }
+
+    if let Some(part) = current_part {
+        schematic.parts.push(part);
+    }
+
+    Ok(schematic)
 }
 
 pub fn collect_schematic_symbols(input: &str) -> Vec<SchematicSymbol> {
-    input
-        .replace('.', " ")
-        .lines()
-        .enumerate()
-        .flat_map(|(row_num, line)| {
-            line.chars()
-                .enumerate()
-                .filter(|(_pos, c)| !c.is_alphanumeric() && !c.is_whitespace())
-                .map(|(pos, char)| SchematicSymbol {
-                    row_num: row_num as u16,
-                    pos: pos as u16,
-                    char,
-                })
-                .collect::<Vec<_>>()
-        })
-        .collect()
+    scan_schematic(input)
+        .expect("Could not scan the schematic")
+        .symbols
 }
 
 pub fn collect_schematic_parts(input: &str) -> Vec<SchematicPart> {
-    let re = Regex::new(r"\d+").unwrap();
-    input
-        .lines()
-        .enumerate()
-        .flat_map(|(row_num, line)| {
-            re.find_iter(line)
-                .map(|m| SchematicPart {
-                    id: m.as_str().parse().unwrap(),
-                    row_num: row_num as u16,
-                    start_pos: m.start() as u16,
-                    end_pos: m.end() as u16,
-                })
-                .collect::<Vec<SchematicPart>>()
-        })
-        .collect()
+    scan_schematic(input)
+        .expect("Could not scan the schematic")
+        .parts
 }
 
 #[cfg(test)]
@@ -144,4 +200,56 @@ mod tests {
             res.iter().map(|p| p.row_num).collect::<Vec<u16>>(),
         );
     }
+
+    #[test]
+    fn test_scan_schematic_crlf() -> anyhow::Result<()> {
+        let schematic = scan_schematic("467..114..\r\n...*......\r\n..35..633.\r\n")?;
+        assert_equal(
+            vec![(0, 0, 3), (0, 5, 8), (2, 2, 4), (2, 6, 9)],
+            schematic
+                .parts
+                .iter()
+                .map(|p| (p.row_num, p.start_pos, p.end_pos)),
+        );
+        assert_equal(
+            vec![('*', 1, 3)],
+            schematic.symbols.iter().map(|s| (s.char, s.row_num, s.pos)),
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_schematic_unicode_symbols() -> anyhow::Result<()> {
+        let schematic = scan_schematic("..€12.\n....√.")?;
+        assert_equal(
+            vec![('€', 0, 2), ('√', 1, 4)],
+            schematic.symbols.iter().map(|s| (s.char, s.row_num, s.pos)),
+        );
+        assert_equal(
+            vec![(12, 3, 5)],
+            schematic
+                .parts
+                .iter()
+                .map(|p| (p.id, p.start_pos, p.end_pos)),
+        );
+        assert!(schematic.parts[0].is_engine_part(&schematic.symbols));
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_schematic_part_too_large() {
+        assert!(scan_schematic("..99999999..").is_err());
+    }
+
+    #[test]
+    fn test_scan_schematic_long_rows() -> anyhow::Result<()> {
+        let row = ".".repeat(u16::MAX as usize - 2) + "*1";
+        let schematic = scan_schematic(&row)?;
+        assert_eq!(u16::MAX, schematic.parts[0].end_pos);
+
+        assert!(scan_schematic(&".".repeat(70_000)).is_err());
+        assert!(scan_schematic(&(".".repeat(u16::MAX as usize) + "1")).is_err());
+        assert!(scan_schematic(&"\n".repeat(70_000)).is_err());
+        Ok(())
+    }
 }
@@ -1,27 +1,17 @@
-use crate::part1::{collect_schematic_parts, collect_schematic_symbols};
+use crate::{
+    gear_rules::{Aggregate, GearRule},
+    part1::{scan_schematic, Schematic},
+};
 
 #[tracing::instrument]
 pub fn solve(input: &str) -> anyhow::Result<String> {
-    let symbols = collect_schematic_symbols(input);
-    let schematic_parts = collect_schematic_parts(input);
+    let Schematic {
+        parts: schematic_parts,
+        symbols,
+    } = scan_schematic(input)?;
 
-    let asterisks = symbols.iter().filter(|s| s.char == '*').collect::<Vec<_>>();
-
-    let result: u32 = asterisks
-        .into_iter()
-        .filter_map(|s| {
-            let gear_parts = schematic_parts
-                .iter()
-                .filter(|p| p.is_engine_part(&[s.to_owned()]))
-                .map(|p| p.id as u32)
-                .collect::<Vec<_>>();
-
-            match gear_parts.len() {
-                2 => Some(gear_parts.iter().product::<u32>()),
-                _ => None,
-            }
-        })
-        .sum();
+    let gear_rule = GearRule::new(['*'], 2..=2, Aggregate::Product);
+    let result = gear_rule.total(&symbols, &schematic_parts)?;
 
     Ok(result.to_string())
 }
@@ -0,0 +1,275 @@
+use std::collections::HashMap;
+
+use crate::{
+    gear_rules::{Aggregate, GearRule},
+    part1::{scan_schematic, Schematic},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SegmentKind {
+    EnginePart,
+    NonPart,
+    Symbol,
+    Gear,
+    Blank,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment {
+    pub kind: SegmentKind,
+    pub text: String,
+    pub info: Option<String>,
+}
+
+pub fn annotate_schematic(input: &str) -> anyhow::Result<Vec<Vec<Segment>>> {
+    let Schematic { parts, symbols } = scan_schematic(input)?;
+    let gears = GearRule::new(['*'], 2..=2, Aggregate::Product).matches(&symbols, &parts)?;
+
+    let gear_info: HashMap<(u16, u16), String> = gears
+        .iter()
+        .map(|g| {
+            let ids = g.parts.iter().map(|p| p.id.to_string()).collect::<Vec<_>>();
+            (
+                (g.symbol.row_num, g.symbol.pos),
+                format!("gear {} = {}", ids.join(" * "), g.value),
+            )
+        })
+        .collect();
+    let parts_by_start: HashMap<(u16, u16), _> = parts
+        .iter()
+        .map(|p| ((p.row_num, p.start_pos), p))
+        .collect();
+    let symbols_by_pos: HashMap<(u16, u16), _> =
+        symbols.iter().map(|s| ((s.row_num, s.pos), s)).collect();
+
+    let rows = input
+        .lines()
+        .enumerate()
+        .map(|(row_num, line)| {
+            let row_num = row_num as u16;
+            let chars = line.chars().collect::<Vec<_>>();
+            let mut segments: Vec<Segment> = Vec::new();
+            let mut pos = 0;
+
+            while pos < chars.len() {
+                let key = (row_num, pos as u16);
+                if let Some(part) = parts_by_start.get(&key) {
+                    let end = part.end_pos as usize;
+                    let (kind, label) = match part.is_engine_part(&symbols) {
+                        true => (SegmentKind::EnginePart, "part"),
+                        false => (SegmentKind::NonPart, "not a part"),
+                    };
+                    segments.push(Segment {
+                        kind,
+                        text: chars[pos..end].iter().collect(),
+                        info: Some(format!(
+                            "{} {} at row {}, cols {}-{}",
+                            label,
+                            part.id,
+                            row_num,
+                            pos,
+                            end - 1
+                        )),
+                    });
+                    pos = end;
+                    continue;
+                }
+
+                if let Some(symbol) = symbols_by_pos.get(&key) {
+                    let (kind, info) = match gear_info.get(&key) {
+                        Some(info) => (SegmentKind::Gear, info.to_owned()),
+                        None => (SegmentKind::Symbol, format!("symbol {:?}", symbol.char)),
+                    };
+                    segments.push(Segment {
+                        kind,
+                        text: symbol.char.to_string(),
+                        info: Some(info),
+                    });
+                    pos += 1;
+                    continue;
+                }
+
+                match segments.last_mut() {
+                    Some(s) if s.kind == SegmentKind::Blank => s.text.push(chars[pos]),
+                    _ => segments.push(Segment {
+                        kind: SegmentKind::Blank,
+                        text: chars[pos].to_string(),
+                        info: None,
+                    }),
+                }
+                pos += 1;
+            }
+
+            segments
+        })
+        .collect();
+
+    Ok(rows)
+}
+
+pub fn render_ansi(input: &str) -> anyhow::Result<String> {
+    let rendered = annotate_schematic(input)?
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|s| match ansi_colour(s.kind) {
+                    Some(colour) => format!("\x1b[{}m{}\x1b[0m", colour, s.text),
+                    None => s.text.to_owned(),
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    Ok(rendered)
+}
+
+fn ansi_colour(kind: SegmentKind) -> Option<&'static str> {
+    match kind {
+        SegmentKind::EnginePart => Some("32"),
+        SegmentKind::NonPart => Some("31"),
+        SegmentKind::Symbol => Some("33"),
+        SegmentKind::Gear => Some("1;36"),
+        SegmentKind::Blank => None,
+    }
+}
+
+pub fn render_html(input: &str) -> anyhow::Result<String> {
+    let body = annotate_schematic(input)?
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|s| {
+                    let text = escape_html(&s.text);
+                    match (html_class(s.kind), &s.info) {
+                        (Some(class), Some(info)) => format!(
+                            "<span class=\"{}\" title=\"{}\">{}</span>",
+                            class,
+                            escape_html(info),
+                            text
+                        ),
+                        _ => text,
+                    }
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    Ok(format!(
+        "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>Engine schematic</title>
+<style>
+body {{ background: #0f0f23; color: #666; }}
+.part {{ color: #2ecc40; }}
+.non-part {{ color: #ff4136; }}
+.symbol {{ color: #ffdc00; }}
+.gear {{ color: #39cccc; font-weight: bold; }}
+span:hover {{ background: #333; }}
+</style>
+</head>
+<body>
+<pre>
+{}
+</pre>
+</body>
+</html>
+",
+        body
+    ))
+}
+
+fn html_class(kind: SegmentKind) -> Option<&'static str> {
+    match kind {
+        SegmentKind::EnginePart => Some("part"),
+        SegmentKind::NonPart => Some("non-part"),
+        SegmentKind::Symbol => Some("symbol"),
+        SegmentKind::Gear => Some("gear"),
+        SegmentKind::Blank => None,
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '&' => "&amp;".to_string(),
+            '<' => "&lt;".to_string(),
+            '>' => "&gt;".to_string(),
+            '"' => "&quot;".to_string(),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::assert_equal;
+
+    use super::*;
+
+    const INPUT: &str = "467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..";
+
+    #[test]
+    fn test_annotate_schematic() -> anyhow::Result<()> {
+        let rows = annotate_schematic(INPUT)?;
+        assert_eq!(10, rows.len());
+        assert_equal(
+            vec![
+                (SegmentKind::EnginePart, "467"),
+                (SegmentKind::Blank, ".."),
+                (SegmentKind::NonPart, "114"),
+                (SegmentKind::Blank, ".."),
+            ],
+            rows[0].iter().map(|s| (s.kind, s.text.as_str())),
+        );
+        assert_equal(
+            vec![
+                (SegmentKind::Blank, "..."),
+                (SegmentKind::Gear, "*"),
+                (SegmentKind::Blank, "......"),
+            ],
+            rows[1].iter().map(|s| (s.kind, s.text.as_str())),
+        );
+        assert_eq!(Some("gear 467 * 35 = 16345".to_string()), rows[1][1].info);
+        assert_eq!(SegmentKind::Symbol, rows[4][1].kind);
+        assert_eq!(
+            INPUT,
+            rows.iter()
+                .map(|r| r.iter().map(|s| s.text.as_str()).collect::<String>())
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_ansi() -> anyhow::Result<()> {
+        let rendered = render_ansi("467.\n...#\n..58")?;
+        assert_eq!(
+            "\x1b[32m467\x1b[0m.\n...\x1b[33m#\x1b[0m\n..\x1b[32m58\x1b[0m",
+            rendered
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_html() -> anyhow::Result<()> {
+        let rendered = render_html("12..\n..&.")?;
+        assert!(rendered
+            .contains("<span class=\"part\" title=\"part 12 at row 0, cols 0-1\">12</span>"));
+        assert!(rendered.contains("<span class=\"symbol\" title=\"symbol '&amp;'\">&amp;</span>"));
+        Ok(())
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::HashSet;
+
+use crate::part1::{parse_card_numbers, Card, Scratchcard};
+
+// numbers below 128 live in a bitset, anything bigger falls back to a sorted list
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NumberSet {
+    low: u128,
+    high: Vec<u32>,
+}
+
+impl NumberSet {
+    pub fn insert(&mut self, num: u32) {
+        match num < 128 {
+            true => self.low |= 1 << num,
+            false => {
+                if let Err(pos) = self.high.binary_search(&num) {
+                    self.high.insert(pos, num);
+                }
+            }
+        }
+    }
+
+    pub fn contains(&self, num: u32) -> bool {
+        match num < 128 {
+            true => self.low & (1 << num) != 0,
+            false => self.high.binary_search(&num).is_ok(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.low.count_ones() as usize + self.high.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn intersection_len(&self, other: &NumberSet) -> usize {
+        let low = (self.low & other.low).count_ones() as usize;
+        let high = self
+            .high
+            .iter()
+            .filter(|num| other.high.binary_search(num).is_ok())
+            .count();
+        low + high
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = u32> + '_ {
+        (0..128u32)
+            .filter(|num| self.low & (1 << num) != 0)
+            .chain(self.high.iter().copied())
+    }
+}
+
+impl FromIterator<u32> for NumberSet {
+    fn from_iter<T: IntoIterator<Item = u32>>(iter: T) -> Self {
+        iter.into_iter().fold(NumberSet::default(), |mut acc, num| {
+            acc.insert(num);
+            acc
+        })
+    }
+}
+
+impl From<&HashSet<u32>> for NumberSet {
+    fn from(set: &HashSet<u32>) -> Self {
+        set.iter().copied().collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompactCard {
+    pub id: u32,
+    pub winning_numbers: NumberSet,
+    pub card_numbers: NumberSet,
+}
+
+impl Scratchcard for CompactCard {
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    fn match_count(&self) -> usize {
+        self.winning_numbers.intersection_len(&self.card_numbers)
+    }
+}
+
+impl From<&Card> for CompactCard {
+    fn from(card: &Card) -> Self {
+        CompactCard {
+            id: card.id,
+            winning_numbers: NumberSet::from(&card.winning_numbers),
+            card_numbers: NumberSet::from(&card.card_numbers),
+        }
+    }
+}
+
+pub fn parse_compact_card(input: &str) -> anyhow::Result<CompactCard> {
+    let (id, winning_numbers, card_numbers) = parse_card_numbers(input)?;
+
+    Ok(CompactCard {
+        id,
+        winning_numbers: NumberSet::from_iter(winning_numbers),
+        card_numbers: NumberSet::from_iter(card_numbers),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::assert_equal;
+
+    use super::*;
+    use crate::part1::parse_card;
+
+    #[test]
+    fn test_number_set() {
+        let mut set = NumberSet::from_iter(vec![5, 127, 128, 1000, 5]);
+        assert_eq!(4, set.len());
+        assert!(set.contains(127));
+        assert!(set.contains(1000));
+        assert!(!set.contains(6));
+
+        set.insert(129);
+        set.insert(0);
+        assert_equal(vec![0, 5, 127, 128, 129, 1000], set.iter());
+    }
+
+    #[test]
+    fn test_intersection_len() {
+        let a = NumberSet::from_iter(vec![1, 17, 64, 127, 200, 4000]);
+        let b = NumberSet::from_iter(vec![2, 17, 127, 128, 4000, 5000]);
+        assert_eq!(3, a.intersection_len(&b));
+        assert_eq!(3, b.intersection_len(&a));
+        assert_eq!(0, a.intersection_len(&NumberSet::default()));
+    }
+
+    #[test]
+    fn test_compact_card_matches_card() -> anyhow::Result<()> {
+        let input = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11
+Card 7: 130 250 999 3 | 999 4 130 3 131";
+        for line in input.lines() {
+            let card = parse_card(line)?;
+            let compact = parse_compact_card(line)?;
+            assert_eq!(compact, CompactCard::from(&card));
+            assert_eq!(card.matches().len(), compact.match_count());
+            assert_eq!(card.points(), compact.points());
+        }
+        Ok(())
+    }
+}
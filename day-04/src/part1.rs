@@ -1,17 +1,21 @@
 use anyhow::anyhow;
+use itertools::Itertools;
 use nom::{
     bytes::complete::tag,
     character::complete::{space1, u32},
     multi::separated_list1,
     sequence::{delimited, separated_pair, tuple},
 };
-use std::collections::HashSet;
+use num::{BigUint, One, Zero};
+use std::{collections::HashSet, fmt::Display, ops::RangeInclusive};
+
+use crate::compact::parse_compact_card;
 
 #[tracing::instrument]
 pub fn solve(input: &str) -> anyhow::Result<String> {
-    let total_points: u32 = input
+    let total_points: BigUint = input
         .lines()
-        .flat_map(parse_card)
+        .flat_map(parse_compact_card)
         .map(|card| card.points())
         .sum();
     Ok(total_points.to_string())
@@ -32,18 +36,47 @@ impl Card {
             .collect()
     }
 
-    pub fn points(&self) -> u32 {
-        let matching = self.matches();
+    pub fn points(&self) -> BigUint {
+        Scratchcard::points(self)
+    }
+}
+
+pub trait Scratchcard {
+    fn id(&self) -> u32;
+    fn match_count(&self) -> usize;
 
-        match matching.len() {
-            0 => 0,
-            1 => 1,
-            len => 2u32.pow(len as u32 - 1),
+    fn points(&self) -> BigUint {
+        match self.match_count() {
+            0 => BigUint::zero(),
+            len => BigUint::one() << (len - 1),
         }
     }
 }
 
+impl Scratchcard for Card {
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    fn match_count(&self) -> usize {
+        self.winning_numbers
+            .iter()
+            .filter(|n| self.card_numbers.contains(n))
+            .count()
+    }
+}
+
 pub fn parse_card(input: &str) -> anyhow::Result<Card> {
+    let (id, winning_numbers, card_numbers) = parse_card_numbers(input)?;
+
+    Ok(Card {
+        id,
+        winning_numbers: HashSet::from_iter(winning_numbers),
+        card_numbers: HashSet::from_iter(card_numbers),
+    })
+}
+
+pub fn parse_card_numbers(input: &str) -> anyhow::Result<(u32, Vec<u32>, Vec<u32>)> {
     let (rest, id) = delimited(
         tuple((tag("Card"), space1)),
         u32::<_, nom::error::Error<_>>,
@@ -58,11 +91,65 @@ pub fn parse_card(input: &str) -> anyhow::Result<Card> {
     )(rest)
     .map_err(|e: nom::Err<_>| anyhow!("Could not parse card numbers: {}", e))?;
 
-    Ok(Card {
-        id,
-        winning_numbers: HashSet::from_iter(winning_numbers),
-        card_numbers: HashSet::from_iter(card_numbers),
-    })
+    Ok((id, winning_numbers, card_numbers))
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CardIdIssue {
+    Duplicate(u32),
+    Missing(RangeInclusive<u32>),
+    OutOfOrder { id: u32, after: u32 },
+}
+
+impl Display for CardIdIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CardIdIssue::Duplicate(id) => write!(f, "Card {} appears more than once", id),
+            CardIdIssue::Missing(ids) if ids.start() == ids.end() => {
+                write!(f, "Card {} is missing", ids.start())
+            }
+            CardIdIssue::Missing(ids) => {
+                write!(f, "Cards {} to {} are missing", ids.start(), ids.end())
+            }
+            CardIdIssue::OutOfOrder { id, after } => {
+                write!(f, "Card {} comes after card {}", id, after)
+            }
+        }
+    }
+}
+
+pub fn validate_card_ids<C: Scratchcard>(cards: &[C]) -> Vec<CardIdIssue> {
+    let mut issues = Vec::new();
+    let mut seen = HashSet::new();
+
+    for (prev, card) in std::iter::once(None)
+        .chain(cards.iter().map(Some))
+        .zip(cards)
+    {
+        if !seen.insert(card.id()) {
+            issues.push(CardIdIssue::Duplicate(card.id()));
+        }
+        if let Some(prev) = prev {
+            if card.id() < prev.id() {
+                issues.push(CardIdIssue::OutOfOrder {
+                    id: card.id(),
+                    after: prev.id(),
+                });
+            }
+        }
+    }
+
+    // one issue per gap between the ids that are there, however many cards it spans
+    let mut next_id = Some(1);
+    for id in seen.into_iter().sorted() {
+        let Some(expected) = next_id else { break };
+        if id > expected {
+            issues.push(CardIdIssue::Missing(expected..=id - 1));
+        }
+        next_id = id.checked_add(1);
+    }
+
+    issues
 }
 
 #[cfg(test)]
@@ -112,6 +199,65 @@ Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
             winning_numbers: HashSet::from_iter(vec![41, 48, 83, 86, 17]),
             card_numbers: HashSet::from_iter(vec![83, 86, 6, 31, 17, 9, 48, 53]),
         };
-        assert_eq!(8, card.points());
+        assert_eq!(BigUint::from(8u32), card.points());
+    }
+
+    #[test]
+    fn test_points_calculation_many_matches() {
+        let card = Card {
+            id: 1,
+            winning_numbers: HashSet::from_iter(1..=300),
+            card_numbers: HashSet::from_iter(101..=500),
+        };
+        assert_eq!(BigUint::one() << 199, card.points());
+        assert_eq!(
+            "803469022129495137770981046170581301261101496891396417650688",
+            card.points().to_string()
+        );
+    }
+
+    #[test]
+    fn test_validate_card_ids() -> anyhow::Result<()> {
+        let cards = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11"
+            .lines()
+            .map(parse_card)
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        assert_eq!(
+            vec![
+                CardIdIssue::OutOfOrder { id: 2, after: 4 },
+                CardIdIssue::Duplicate(4),
+                CardIdIssue::Missing(3..=3),
+                CardIdIssue::Missing(5..=5),
+            ],
+            validate_card_ids(&cards)
+        );
+        assert!(validate_card_ids(&cards[..1]).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_card_ids_large_gaps() -> anyhow::Result<()> {
+        let cards = "Card 50000000: 1 2 | 1 3
+Card 4294967295: 1 2 | 1 3"
+            .lines()
+            .map(parse_card)
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let issues = validate_card_ids(&cards);
+        assert_eq!(
+            vec![
+                CardIdIssue::Missing(1..=49_999_999),
+                CardIdIssue::Missing(50_000_001..=4_294_967_294),
+            ],
+            issues
+        );
+        assert_eq!("Cards 1 to 49999999 are missing", issues[0].to_string());
+        assert_eq!("Card 3 is missing", CardIdIssue::Missing(3..=3).to_string());
+        Ok(())
     }
 }
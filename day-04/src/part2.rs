@@ -1,34 +1,62 @@
-use itertools::put_back_n;
+use std::collections::BTreeMap;
 
-use crate::part1::{parse_card, Card};
+use anyhow::anyhow;
+use num::{BigUint, One};
+
+use crate::{
+    compact::parse_compact_card,
+    part1::{validate_card_ids, Scratchcard},
+};
 
 #[tracing::instrument]
 pub fn solve(input: &str) -> anyhow::Result<String> {
-    let cards: Vec<Card> = input.lines().flat_map(parse_card).collect();
-    let mut new_cards_count: usize = 0;
-
-    let mut orig_cards_it = put_back_n(cards.iter());
-
-    // ヽ(￣(ｴ)￣)ﾉ
-    // very slow
-    while let Some(c) = orig_cards_it.next() {
-        let num_new_cards = c.matches().len();
-        new_cards_count += num_new_cards;
-        cards
-            .iter()
-            .skip(c.id as usize)
-            .take(num_new_cards)
-            .for_each(|new_card| {
-                orig_cards_it.put_back(new_card)
-            });
-    }
-
-    Ok((new_cards_count + cards.len()).to_string())
+    let cards: Vec<_> = input.lines().flat_map(parse_compact_card).collect();
+    for issue in validate_card_ids(&cards) {
+        tracing::warn!("{}", issue);
+    }
+
+    let total: BigUint = count_copies(&cards)?.values().sum();
+
+    Ok(total.to_string())
+}
+
+// every card only ever adds copies to the cards with a higher id,
+// so a single pass in id order is enough to know how many of each we end up with
+pub fn count_copies<C: Scratchcard>(cards: &[C]) -> anyhow::Result<BTreeMap<u32, BigUint>> {
+    let mut cards_by_id = BTreeMap::new();
+    for card in cards {
+        if cards_by_id.insert(card.id(), card).is_some() {
+            return Err(anyhow!("Card {} appears more than once", card.id()));
+        }
+    }
+
+    let mut copies: BTreeMap<u32, BigUint> =
+        cards_by_id.keys().map(|id| (*id, BigUint::one())).collect();
+
+    for (id, card) in cards_by_id {
+        let num_new_cards = card.match_count() as u32;
+        // nothing comes after the highest possible id to win copies of
+        let Some(next_id) = id.checked_add(1) else {
+            continue;
+        };
+        if num_new_cards == 0 {
+            continue;
+        }
+        let card_copies = copies[&id].clone();
+        copies
+            .range_mut(next_id..=id.saturating_add(num_new_cards))
+            .for_each(|(_, c)| *c += &card_copies);
+    }
+
+    Ok(copies)
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashSet;
+
     use super::*;
+    use crate::part1::{parse_card, Card};
 
     #[test]
     fn test_solve() -> anyhow::Result<()> {
@@ -41,4 +69,107 @@ Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
         assert_eq!("30", solve(input)?);
         Ok(())
     }
+
+    #[test]
+    fn test_count_copies() -> anyhow::Result<()> {
+        let cards = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11"
+            .lines()
+            .map(parse_card)
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        assert_eq!(
+            (1..=6)
+                .zip([1u32, 2, 4, 8, 14, 1].map(BigUint::from))
+                .collect::<BTreeMap<_, _>>(),
+            count_copies(&cards)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_solve_shuffled() -> anyhow::Result<()> {
+        let input = "Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11
+Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19";
+        assert_eq!("30", solve(input)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_solve_sparse() -> anyhow::Result<()> {
+        let input = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
+        assert_eq!("14", solve(input)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_count_copies_duplicate_ids() -> anyhow::Result<()> {
+        let cards = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 1: 13 32 20 16 61 | 61 30 68 82 17 32 24 19"
+            .lines()
+            .map(parse_card)
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        assert!(count_copies(&cards).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_count_copies_last_possible_id() -> anyhow::Result<()> {
+        let cards = "Card 4294967294: 1 2 | 1 3
+Card 4294967295: 1 2 | 1 2"
+            .lines()
+            .map(parse_card)
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let copies = count_copies(&cards)?;
+        assert_eq!(BigUint::one(), copies[&4_294_967_294]);
+        assert_eq!(BigUint::from(2u32), copies[&u32::MAX]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_count_copies_many_cards() {
+        let cards = (1..=50_000)
+            .map(|id| Card {
+                id,
+                winning_numbers: HashSet::from_iter(vec![1, 2]),
+                card_numbers: HashSet::from_iter(vec![1, 3]),
+            })
+            .collect::<Vec<_>>();
+
+        let copies = count_copies(&cards).unwrap();
+        assert_eq!(BigUint::from(50_000u32), copies[&50_000]);
+        assert_eq!(
+            BigUint::from(50_000u64 * 50_001 / 2),
+            copies.values().sum::<BigUint>()
+        );
+    }
+
+    #[test]
+    fn test_count_copies_beyond_usize() {
+        let cards = (1..=200)
+            .map(|id| Card {
+                id,
+                winning_numbers: HashSet::from_iter(1..=300),
+                card_numbers: HashSet::from_iter(1..=300),
+            })
+            .collect::<Vec<_>>();
+
+        let copies = count_copies(&cards).unwrap();
+        assert_eq!(BigUint::one() << 199, copies[&200]);
+        assert_eq!(
+            (BigUint::one() << 200) - BigUint::one(),
+            copies.values().sum::<BigUint>()
+        );
+    }
 }
@@ -0,0 +1,14 @@
+use day_05::{
+    lint::lint_report,
+    part1::{parse_almanac, SeedMode},
+};
+
+#[tracing::instrument]
+fn main() -> anyhow::Result<()> {
+    tracing_subscriber::fmt::init();
+
+    let file = include_str!("../../input1.txt");
+    let almanac = parse_almanac(file, SeedMode::Values)?;
+    println!("{}", lint_report(&almanac.maps));
+    Ok(())
+}
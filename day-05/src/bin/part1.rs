@@ -8,4 +8,4 @@ fn main() -> anyhow::Result<()> {
     let result = solve(file)?;
     println!("SOLUTION: {}", result);
     Ok(())
-}
\ No newline at end of file
+}
@@ -1,11 +1,16 @@
-use day_05::part2::solve;
+use day_05::part2::{solve_with, SearchStrategy};
 
 #[tracing::instrument]
 fn main() -> anyhow::Result<()> {
     tracing_subscriber::fmt::init();
 
+    let strategy = match std::env::args().nth(1) {
+        Some(arg) => arg.parse()?,
+        None => SearchStrategy::Forward,
+    };
+
     let file = include_str!("../../input2.txt");
-    let result = solve(file)?;
+    let result = solve_with(file, strategy)?;
     println!("SOLUTION: {}", result);
     Ok(())
-}
\ No newline at end of file
+}
@@ -1,2 +1,5 @@
+pub mod lint;
 pub mod part1;
-pub mod part2;
\ No newline at end of file
+pub mod part2;
+pub mod piecewise;
+pub mod query;
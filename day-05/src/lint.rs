@@ -0,0 +1,185 @@
+use std::{collections::HashMap, fmt, ops::Range};
+
+use crate::part1::AlmanacMap;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MapIssue {
+    OverlappingSources {
+        first: Range<u64>,
+        second: Range<u64>,
+    },
+    OverlappingDestinations {
+        first: Range<u64>,
+        second: Range<u64>,
+    },
+    Gap(Range<u64>),
+    IdentitySegment(Range<u64>),
+}
+
+impl fmt::Display for MapIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MapIssue::OverlappingSources { first, second } => write!(
+                f,
+                "sources {:?} and {:?} overlap on {:?}",
+                first,
+                second,
+                overlap(first, second)
+            ),
+            MapIssue::OverlappingDestinations { first, second } => write!(
+                f,
+                "destinations {:?} and {:?} overlap on {:?}",
+                first,
+                second,
+                overlap(first, second)
+            ),
+            MapIssue::Gap(range) => write!(f, "nothing maps {:?}", range),
+            MapIssue::IdentitySegment(range) => write!(f, "{:?} maps onto itself", range),
+        }
+    }
+}
+
+fn overlap(a: &Range<u64>, b: &Range<u64>) -> Range<u64> {
+    a.start.max(b.start)..a.end.min(b.end)
+}
+
+fn overlapping_pairs<'a>(
+    ranges: &'a [&'a Range<u64>],
+) -> impl Iterator<Item = (Range<u64>, Range<u64>)> + 'a {
+    ranges.iter().enumerate().flat_map(move |(i, first)| {
+        ranges[i + 1..]
+            .iter()
+            .filter(move |second| !overlap(first, second).is_empty())
+            .map(move |second| ((*first).clone(), (*second).clone()))
+    })
+}
+
+// only the stretches between the lowest and highest source count as gaps,
+// everything outside of them is passed through as expected
+fn gaps(sources: &[&Range<u64>]) -> Vec<Range<u64>> {
+    let mut sorted = sources
+        .iter()
+        .filter(|r| !r.is_empty())
+        .copied()
+        .collect::<Vec<_>>();
+    sorted.sort_by_key(|r| r.start);
+
+    let mut gaps = Vec::new();
+    let mut covered_to = match sorted.first() {
+        Some(first) => first.end,
+        None => return gaps,
+    };
+    for range in &sorted[1..] {
+        if range.start > covered_to {
+            gaps.push(covered_to..range.start);
+        }
+        covered_to = covered_to.max(range.end);
+    }
+    gaps
+}
+
+pub fn lint_map(map: &AlmanacMap) -> Vec<MapIssue> {
+    let sources = map.map.iter().map(|(src, _)| src).collect::<Vec<_>>();
+    let destinations = map.map.iter().map(|(_, dest)| dest).collect::<Vec<_>>();
+
+    let mut issues = overlapping_pairs(&sources)
+        .map(|(first, second)| MapIssue::OverlappingSources { first, second })
+        .collect::<Vec<_>>();
+    issues.extend(
+        overlapping_pairs(&destinations)
+            .map(|(first, second)| MapIssue::OverlappingDestinations { first, second }),
+    );
+    issues.extend(gaps(&sources).into_iter().map(MapIssue::Gap));
+    issues.extend(
+        map.map
+            .iter()
+            .filter(|(src, dest)| !src.is_empty() && src.start == dest.start)
+            .map(|(src, _)| MapIssue::IdentitySegment(src.clone())),
+    );
+    issues
+}
+
+pub fn lint_report(maps: &HashMap<String, AlmanacMap>) -> String {
+    let mut sorted = maps.values().collect::<Vec<_>>();
+    sorted.sort_by(|a, b| a.from.cmp(&b.from));
+
+    sorted
+        .into_iter()
+        .map(|map| {
+            let issues = lint_map(map);
+            let header = match issues.len() {
+                0 => format!("{}-to-{}: ok", map.from, map.to),
+                1 => format!("{}-to-{}: 1 issue", map.from, map.to),
+                n => format!("{}-to-{}: {} issues", map.from, map.to, n),
+            };
+            std::iter::once(header)
+                .chain(issues.iter().map(|issue| format!("  {}", issue)))
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::part1::parse_almanac_map;
+
+    #[test]
+    fn test_lint_clean_map() -> anyhow::Result<()> {
+        let (_, map) = parse_almanac_map(
+            "seed-to-soil map:
+50 98 2
+52 50 48",
+        )?;
+        assert_eq!(Vec::<MapIssue>::new(), lint_map(&map));
+        Ok(())
+    }
+
+    #[test]
+    fn test_lint_map() -> anyhow::Result<()> {
+        let (_, map) = parse_almanac_map(
+            "soil-to-fertilizer map:
+100 0 10
+0 5 10
+30 30 5
+105 40 5",
+        )?;
+        assert_eq!(
+            vec![
+                MapIssue::OverlappingSources {
+                    first: 0..10,
+                    second: 5..15
+                },
+                MapIssue::OverlappingDestinations {
+                    first: 100..110,
+                    second: 105..110
+                },
+                MapIssue::Gap(15..30),
+                MapIssue::Gap(35..40),
+                MapIssue::IdentitySegment(30..35),
+            ],
+            lint_map(&map)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_lint_report() -> anyhow::Result<()> {
+        let maps = [
+            "seed-to-soil map:\n50 98 2\n52 50 48",
+            "soil-to-water map:\n7 7 3",
+        ]
+        .into_iter()
+        .map(|m| parse_almanac_map(m).map(|(_, m)| (m.from.to_string(), m)))
+        .collect::<Result<HashMap<_, _>, _>>()?;
+        assert_eq!(
+            "seed-to-soil: ok
+soil-to-water: 1 issue
+  7..10 maps onto itself",
+            lint_report(&maps)
+        );
+        Ok(())
+    }
+}
@@ -2,50 +2,25 @@ use std::{collections::HashMap, ops::Range};
 
 use anyhow::anyhow;
 use nom::{
+    branch::alt,
     bytes::complete::tag,
-    character::complete::{alpha1, line_ending, newline, space0, space1, u64},
-    combinator::opt,
-    multi::{fold_many0, fold_many1, separated_list1},
+    character::complete::{alpha1, line_ending, multispace0, newline, space0, space1, u64},
+    combinator::{eof, map_res, opt},
+    multi::{fold_many0, many1, separated_list1},
     sequence::{preceded, separated_pair, terminated, tuple},
     IResult,
 };
 
+use crate::query::{collect_almanac_maps, map_between, validate_maps};
+
 #[tracing::instrument]
 pub fn solve(input: &str) -> anyhow::Result<String> {
-    let almanac = parse_almanac(input)?;
+    let almanac = parse_almanac(input, SeedMode::Values)?;
     tracing::info!("Parsed the almanac");
 
-    let mut from = "seed";
-    let target = "location";
-    let mut buff: Vec<u64> = almanac.seeds;
-
-    while from != target {
-        match almanac.maps.get(from) {
-            None => break,
-            Some(map) => {
-                println!("from/to {} {}", from, map.to);
-                from = &map.to;
-                buff = buff
-                    .iter()
-                    .map(|num| {
-                        map.map
-                            .iter()
-                            // .filter(|(src, dest)| )
-                            .find_map(|(src, dest)| match src.contains(num) {
-                                false => None,
-                                true => {
-                                    let pos = num - src.start;
-                                    Some(dest.start + pos)
-                                }
-                            })
-                            .unwrap_or(*num)
-                    })
-                    .collect();
-            }
-        }
-    }
+    let locations = map_between(&almanac.maps, "seed", "location", almanac.seed_values())?;
 
-    Ok(buff.iter().min().unwrap_or(&0u64).to_string())
+    Ok(locations.iter().min().unwrap_or(&0u64).to_string())
 }
 
 #[derive(Debug)]
@@ -55,26 +30,92 @@ pub struct AlmanacMap {
     pub map: Vec<(Range<u64>, Range<u64>)>,
 }
 
+impl AlmanacMap {
+    pub fn map_value(&self, num: u64) -> u64 {
+        self.map
+            .iter()
+            .find_map(|(src, dest)| match src.contains(&num) {
+                false => None,
+                true => Some(dest.start + (num - src.start)),
+            })
+            .unwrap_or(num)
+    }
+
+    // splits the range into the pieces covered by each mapping and the pieces no mapping
+    // touches, the earlier mapping wins where sources overlap just like in `map_value`
+    pub fn map_range(&self, range: Range<u64>) -> Vec<Range<u64>> {
+        let mut mapped = Vec::new();
+        let mut unmapped = vec![range];
+
+        for (src, dest) in &self.map {
+            unmapped = unmapped
+                .into_iter()
+                .flat_map(|r| {
+                    let start = r.start.max(src.start);
+                    let end = r.end.min(src.end);
+                    if start >= end {
+                        return vec![r];
+                    }
+
+                    mapped.push(dest.start + (start - src.start)..dest.start + (end - src.start));
+                    [r.start..start, end..r.end]
+                        .into_iter()
+                        .filter(|r| !r.is_empty())
+                        .collect()
+                })
+                .collect();
+        }
+
+        mapped.extend(unmapped);
+        mapped.retain(|r| !r.is_empty());
+        mapped
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeedMode {
+    Values,
+    Ranges,
+}
+
 #[derive(Debug)]
 pub struct Almanac {
-    pub seeds: Vec<u64>,
+    pub seeds: Vec<Range<u64>>,
     pub maps: HashMap<String, AlmanacMap>,
 }
 
-pub fn parse_almanac(input: &str) -> anyhow::Result<Almanac> {
-    let (rest, seeds) = terminated(parse_seeds, newline)(input)
+impl Almanac {
+    pub fn seed_values(&self) -> Vec<u64> {
+        self.seeds.iter().flat_map(|r| r.clone()).collect()
+    }
+}
+
+pub fn parse_almanac(input: &str, seed_mode: SeedMode) -> anyhow::Result<Almanac> {
+    let seeds_parser = match seed_mode {
+        SeedMode::Values => |input| {
+            map_res(parse_seeds, |seeds| {
+                seeds
+                    .into_iter()
+                    .map(|seed| checked_range(seed, 1))
+                    .collect::<anyhow::Result<_>>()
+            })(input)
+        },
+        SeedMode::Ranges => parse_seed_ranges,
+    };
+    let (rest, seeds) = terminated(seeds_parser, newline)(input)
         .map_err(|e| anyhow!("Failed to parse seeds: {}", e))?;
     let (rest, _) = newline::<_, nom::error::Error<&str>>(rest)
         .map_err(|_| anyhow!("Missing newline separator"))?;
-    let (_, maps) = fold_many1(
-        terminated(parse_almanac_map, opt(newline)),
-        HashMap::new,
-        |mut acc, v| {
-            acc.insert(v.from.to_string(), v);
-            acc
-        },
-    )(rest)
-    .map_err(|e| anyhow!("Failed to parse almanac maps: {}", e))?;
+    let (rest, maps) = many1(terminated(parse_almanac_map, multispace0))(rest)
+        .map_err(|e| anyhow!("Failed to parse almanac maps: {}", e))?;
+    if !rest.trim().is_empty() {
+        return Err(anyhow!(
+            "Could not parse almanac from {:?}",
+            rest.lines().next().unwrap_or_default()
+        ));
+    }
+    let maps = collect_almanac_maps(maps)?;
+    validate_maps(&maps)?;
 
     Ok(Almanac { seeds, maps })
 }
@@ -83,29 +124,53 @@ fn parse_seeds(input: &str) -> IResult<&str, Vec<u64>> {
     preceded(tuple((tag("seeds:"), space1)), separated_list1(space1, u64))(input)
 }
 
+// a range has to end inside u64, so neither a seed nor a map entry can reach u64::MAX
+fn checked_range(start: u64, len: u64) -> anyhow::Result<Range<u64>> {
+    start
+        .checked_add(len)
+        .map(|end| start..end)
+        .ok_or_else(|| anyhow!("Range {} {} does not fit in a u64", start, len))
+}
+
+fn parse_seed_ranges(input: &str) -> IResult<&str, Vec<Range<u64>>> {
+    let range_parser = map_res(separated_pair(u64, space1, u64), |(start, len)| {
+        checked_range(start, len)
+    });
+    preceded(
+        tuple((tag("seeds:"), space1)),
+        separated_list1(space1, range_parser),
+    )(input)
+}
+
 pub fn parse_almanac_map(input: &str) -> IResult<&str, AlmanacMap> {
     let (rest, (from_name, to_name)) = terminated(
         separated_pair(alpha1, tag("-to-"), alpha1),
-        tuple((space1, tag("map:"), line_ending)),
+        tuple((space1, tag("map:"), alt((line_ending, eof)))),
     )(input)?;
 
     let range_map_parser = terminated(
-        tuple((
-            terminated(u64::<_, nom::error::Error<&str>>, space1),
-            terminated(u64, space1),
-            terminated(u64, space0),
-        )),
+        map_res(
+            tuple((
+                terminated(u64::<_, nom::error::Error<&str>>, space1),
+                terminated(u64, space1),
+                terminated(u64, space0),
+            )),
+            |(dest_start, orig_start, len)| -> anyhow::Result<_> {
+                Ok((
+                    checked_range(orig_start, len)?,
+                    checked_range(dest_start, len)?,
+                ))
+            },
+        ),
         opt(line_ending),
     );
 
-    let (rest, map) = fold_many0(
-        range_map_parser,
-        Vec::new,
-        |mut acc, (dest_start, orig_start, len)| {
-            acc.push((orig_start..orig_start + len, dest_start..dest_start + len));
-            acc
-        },
-    )(rest)?;
+    // an entry that overflows stops the map early, which leaves it unparsed for
+    // `parse_almanac` to reject
+    let (rest, map) = fold_many0(range_map_parser, Vec::new, |mut acc, entry| {
+        acc.push(entry);
+        acc
+    })(rest)?;
 
     Ok((
         rest,
@@ -178,4 +243,73 @@ humidity-to-location map:
         assert_eq!("humidity", res.to);
         assert_eq!(vec![(69..70, 0..1), (0..69, 1..70),], res.map);
     }
+
+    #[test]
+    fn test_map_value() -> anyhow::Result<()> {
+        let (_, map) = parse_almanac_map(
+            "seed-to-soil map:
+50 98 2
+52 50 48",
+        )?;
+        assert_eq!(81, map.map_value(79));
+        assert_eq!(14, map.map_value(14));
+        assert_eq!(51, map.map_value(99));
+        assert_eq!(100, map.map_value(100));
+        Ok(())
+    }
+
+    #[test]
+    fn test_map_range() -> anyhow::Result<()> {
+        let (_, map) = parse_almanac_map(
+            "seed-to-soil map:
+50 98 2
+52 50 48",
+        )?;
+        assert_eq!(vec![81..95], map.map_range(79..93));
+        assert_eq!(vec![52..54, 40..50], map.map_range(40..52));
+        assert_eq!(vec![50..52, 96..100, 100..110], map.map_range(94..110));
+        assert_eq!(vec![0..10], map.map_range(0..10));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_almanac_seed_modes() -> anyhow::Result<()> {
+        let input = "seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48";
+        let almanac = parse_almanac(input, SeedMode::Values)?;
+        assert_eq!(vec![79..80, 14..15, 55..56, 13..14], almanac.seeds);
+        assert_eq!(vec![79, 14, 55, 13], almanac.seed_values());
+
+        let almanac = parse_almanac(input, SeedMode::Ranges)?;
+        assert_eq!(vec![79..93, 55..68], almanac.seeds);
+        assert_eq!(1, almanac.maps.len());
+
+        assert!(parse_almanac(
+            "seeds: 79 14 55\n\nseed-to-soil map:\n50 98 2",
+            SeedMode::Ranges
+        )
+        .is_err());
+
+        let input = "seeds: 1\n\nseed-to-soil map:\n1 2 3\n\nsoil-to-seed map:\n1 2 3";
+        assert!(parse_almanac(input, SeedMode::Values).is_err());
+
+        // blank lines and maps with no entries are fine, anything else left over is not
+        let input = "seeds: 79 14\n\nseed-to-soil map:\n\n\nsoil-to-location map:\n1 2 3\n";
+        assert_eq!(2, parse_almanac(input, SeedMode::Values)?.maps.len());
+        let input = "seeds: 79 14\n\nseed-to-soil map:\n50 98 2\n\nsoil-to-location:\n1 2 3";
+        assert!(parse_almanac(input, SeedMode::Values).is_err());
+
+        let input = "seeds: 18446744073709551615\n\nseed-to-soil map:\n50 98 2";
+        assert!(parse_almanac(input, SeedMode::Values).is_err());
+        let input = "seeds: 1\n\nseed-to-location map:\n0 18446744073709551615 5";
+        assert!(parse_almanac(input, SeedMode::Values).is_err());
+        let input = "seeds: 1\n\nseed-to-location map:\n18446744073709551615 0 5\n1 2 3";
+        assert!(parse_almanac(input, SeedMode::Values).is_err());
+        let input = "seeds: 18446744073709551610 10\n\nseed-to-soil map:\n50 98 2";
+        assert!(parse_almanac(input, SeedMode::Ranges).is_err());
+        Ok(())
+    }
 }
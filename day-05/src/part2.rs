@@ -1,106 +1,103 @@
-use std::{collections::HashMap, ops::Range};
+use std::str::FromStr;
 
 use anyhow::anyhow;
-use nom::{
-    bytes::complete::tag,
-    character::complete::{newline, space1, u64},
-    combinator::{map, opt},
-    multi::{fold_many1, separated_list1},
-    sequence::{preceded, separated_pair, terminated, tuple},
-    IResult,
-};
-use rangetools::Rangetools;
 
-use crate::part1::{parse_almanac_map, AlmanacMap};
+use crate::{
+    part1::{parse_almanac, Almanac, SeedMode},
+    piecewise::compose_chain,
+    query::map_between,
+};
 
 #[tracing::instrument]
 pub fn solve(input: &str) -> anyhow::Result<String> {
-    let almanac = parse_almanac(input)?;
-    tracing::info!("Parsed the almanac");
+    solve_with(input, SearchStrategy::Forward)
+}
 
-    let mut from = "seed";
-    let target = "location";
-    let mut buff: Vec<Range<u64>> = almanac.seeds;
-
-    while from != target {
-        match almanac.maps.get(from) {
-            None => break,
-            Some(map) => {
-                from = &map.to;
-                buff = buff
-                    .iter()
-                    .flat_map(|num_range| {
-                        let new_dest_to_buff_mapped = map
-                            .map
-                            .iter()
-                            .filter_map(|(src, dest)| {
-                                let intersection: Range<u64> =
-                                    match src.clone().intersects(num_range.clone()) {
-                                        true => num_range.clone().intersection(src.clone()).into(),
-                                        false => return None,
-                                    };
-                                let intersection_offset = intersection.start - src.start;
-                                let intersection_len = intersection.end - intersection.start;
-
-                                let dest_intersection = dest.start + intersection_offset
-                                    ..dest.start + intersection_offset + intersection_len - 1;
-
-                                Some(dest_intersection)
-                            })
-                            .collect::<Vec<Range<u64>>>();
-
-                        match new_dest_to_buff_mapped.is_empty() {
-                            true => vec![num_range.clone()],
-                            false => new_dest_to_buff_mapped,
-                        }
-                    })
-                    .collect();
-            }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchStrategy {
+    Forward,
+    Reverse,
+    Checked,
+}
+
+impl FromStr for SearchStrategy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "forward" => Ok(Self::Forward),
+            "reverse" => Ok(Self::Reverse),
+            "checked" => Ok(Self::Checked),
+            v => Err(anyhow!("Unknown search strategy: {:?}", v)),
         }
     }
+}
 
-    let min = buff.iter().map(|v| v.start).min().unwrap_or(0);
+#[tracing::instrument]
+pub fn solve_with(input: &str, strategy: SearchStrategy) -> anyhow::Result<String> {
+    let almanac = parse_almanac(input, SeedMode::Ranges)?;
+    tracing::info!("Parsed the almanac");
 
-    Ok(min.to_string())
-}
+    let lowest = match strategy {
+        SearchStrategy::Forward => lowest_location(&almanac)?,
+        SearchStrategy::Reverse => lowest_location_reverse(&almanac)?,
+        SearchStrategy::Checked => {
+            let forward = lowest_location(&almanac)?;
+            let reverse = lowest_location_reverse(&almanac)?;
+            if forward != reverse {
+                return Err(anyhow!(
+                    "Forward search found {} but reverse search found {}",
+                    forward,
+                    reverse
+                ));
+            }
+            forward
+        }
+    };
 
-#[derive(Debug)]
-pub struct Almanac {
-    pub seeds: Vec<Range<u64>>,
-    pub maps: HashMap<String, AlmanacMap>,
+    Ok(lowest.to_string())
 }
 
-pub fn parse_almanac(input: &str) -> anyhow::Result<Almanac> {
-    let (rest, seeds) = terminated(parse_seeds, newline)(input)
-        .map_err(|e| anyhow!("Failed to parse seeds: {}", e))?;
-    let (rest, _) = newline::<_, nom::error::Error<&str>>(rest)
-        .map_err(|_| anyhow!("Missing newline separator"))?;
-    let (_, maps) = fold_many1(
-        terminated(parse_almanac_map, opt(newline)),
-        HashMap::new,
-        |mut acc, v| {
-            acc.insert(v.from.to_string(), v);
-            acc
-        },
-    )(rest)
-    .map_err(|e| anyhow!("Failed to parse almanac maps: {}", e))?;
-
-    Ok(Almanac { seeds, maps })
+pub fn lowest_location(almanac: &Almanac) -> anyhow::Result<u64> {
+    let locations = map_between(&almanac.maps, "seed", "location", almanac.seeds.clone())?;
+    Ok(locations.iter().map(|v| v.start).min().unwrap_or(0))
 }
 
-fn parse_seeds(input: &str) -> IResult<&str, Vec<Range<u64>>> {
-    let range_parser = map(separated_pair(u64, space1, u64), |(start, end)| {
-        start..start + end
-    });
-    preceded(
-        tuple((tag("seeds:"), space1)),
-        separated_list1(space1, range_parser),
-    )(input)
+// walks the location segments of the composed map upwards and maps each one back to the
+// seeds it comes from, once a segment starts above the best location found nothing after
+// it can do better
+pub fn lowest_location_reverse(almanac: &Almanac) -> anyhow::Result<u64> {
+    if almanac.seeds.iter().all(|r| r.is_empty()) {
+        return Ok(0);
+    }
+    let inverse = compose_chain(&almanac.maps, "seed", "location")?.inverse();
+
+    let mut lowest: Option<u64> = None;
+    for segment in inverse.segments() {
+        if lowest.is_some_and(|lowest| lowest <= segment.dest_start) {
+            break;
+        }
+        let found = almanac
+            .seeds
+            .iter()
+            .filter_map(|seeds| {
+                let start = seeds.start.max(segment.src.start);
+                let end = seeds.end.min(segment.src.end);
+                (start < end).then(|| segment.apply(start))
+            })
+            .min();
+        lowest = lowest.into_iter().chain(found).min();
+    }
+
+    lowest.ok_or_else(|| anyhow!("No location maps back to a seed"))
 }
 
 #[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+
     use super::*;
+    use crate::part1::AlmanacMap;
 
     #[test]
     fn test_solve() -> anyhow::Result<()> {
@@ -140,4 +137,144 @@ humidity-to-location map:
         assert_eq!("46", solve(input)?);
         Ok(())
     }
+
+    fn almanac_map() -> impl Strategy<Value = Vec<(u64, u64, u64)>> {
+        prop::collection::vec((0u64..200, 0u64..200, 1u64..60), 0..6)
+    }
+
+    #[test]
+    fn test_solve_reverse() -> anyhow::Result<()> {
+        let input = "seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4
+
+water-to-light map:
+88 18 7
+18 25 70
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
+
+temperature-to-humidity map:
+0 69 1
+1 0 69
+
+humidity-to-location map:
+60 56 37
+56 93 4";
+        assert_eq!("46", solve_with(input, SearchStrategy::Reverse)?);
+        assert_eq!("46", solve_with(input, SearchStrategy::Checked)?);
+
+        // the lowest location is far too high to reach one location at a time
+        let input = "seeds: 10 5 3000 2\n\nseed-to-location map:\n1000000000000 0 100";
+        assert_eq!("3000", solve_with(input, SearchStrategy::Checked)?);
+        let input = "seeds: 10 5\n\nseed-to-location map:\n1000000000000 0 100";
+        assert_eq!("1000000000010", solve_with(input, SearchStrategy::Checked)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_search_strategy() {
+        assert_eq!(
+            Ok(SearchStrategy::Reverse),
+            "reverse".parse().map_err(|_| ())
+        );
+        assert!("sideways".parse::<SearchStrategy>().is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn test_map_range_matches_map_value(
+            entries in almanac_map(),
+            start in 0u64..250,
+            len in 1u64..80,
+        ) {
+            let map = AlmanacMap {
+                from: "seed".to_string(),
+                to: "soil".to_string(),
+                map: entries
+                    .into_iter()
+                    .map(|(dest, src, len)| (src..src + len, dest..dest + len))
+                    .collect(),
+            };
+
+            let mut expected = (start..start + len)
+                .map(|v| map.map_value(v))
+                .collect::<Vec<_>>();
+            expected.sort();
+            let mut mapped = map
+                .map_range(start..start + len)
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>();
+            mapped.sort();
+
+            prop_assert_eq!(expected, mapped);
+        }
+
+        #[test]
+        fn test_solve_matches_brute_force(
+            seeds in prop::collection::vec((0u64..250, 1u64..40), 1..4),
+            maps in prop::collection::vec(almanac_map(), 1..5),
+        ) {
+            let categories = std::iter::once("seed")
+                .chain(["soil", "fertilizer", "water", "light"][..maps.len() - 1].iter().copied())
+                .chain(std::iter::once("location"))
+                .collect::<Vec<_>>();
+            let maps_text = maps
+                .iter()
+                .enumerate()
+                .map(|(i, entries)| {
+                    let lines = entries
+                        .iter()
+                        .map(|(dest, src, len)| format!("{} {} {}", dest, src, len))
+                        .collect::<Vec<_>>();
+                    format!(
+                        "{}-to-{} map:\n{}",
+                        categories[i],
+                        categories[i + 1],
+                        lines.join("\n")
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n\n");
+
+            let seed_ranges = seeds
+                .iter()
+                .map(|(start, len)| format!("{} {}", start, len))
+                .collect::<Vec<_>>();
+            let seed_values = seeds
+                .iter()
+                .flat_map(|(start, len)| *start..start + len)
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>();
+
+            let range_input = format!("seeds: {}\n\n{}", seed_ranges.join(" "), maps_text);
+            let brute_force_input = format!("seeds: {}\n\n{}", seed_values.join(" "), maps_text);
+
+            prop_assert_eq!(
+                crate::part1::solve(&brute_force_input).unwrap(),
+                solve(&range_input).unwrap()
+            );
+            prop_assert_eq!(
+                solve(&range_input).unwrap(),
+                solve_with(&range_input, SearchStrategy::Reverse).unwrap()
+            );
+        }
+    }
 }
@@ -0,0 +1,347 @@
+use std::{collections::HashMap, ops::Range};
+
+use crate::{part1::AlmanacMap, query::resolve_path};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment {
+    pub src: Range<u64>,
+    pub dest_start: u64,
+}
+
+impl Segment {
+    pub fn dest(&self) -> Range<u64> {
+        let len = self.src.end - self.src.start;
+        self.dest_start..self.dest_start.saturating_add(len)
+    }
+
+    pub fn apply(&self, num: u64) -> u64 {
+        self.dest_start + (num - self.src.start)
+    }
+}
+
+// the segments are sorted and cover the whole 0..u64::MAX domain without gaps,
+// unmapped stretches are identity segments
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PiecewiseMap {
+    segments: Vec<Segment>,
+}
+
+impl PiecewiseMap {
+    pub fn identity() -> Self {
+        PiecewiseMap {
+            segments: vec![Segment {
+                src: 0..u64::MAX,
+                dest_start: 0,
+            }],
+        }
+    }
+
+    fn from_starts(starts: Vec<u64>, map_value: impl Fn(u64) -> u64) -> Self {
+        let ends = starts.iter().skip(1).copied().chain([u64::MAX]);
+        let segments = starts
+            .iter()
+            .zip(ends)
+            .map(|(start, end)| Segment {
+                src: *start..end,
+                dest_start: map_value(*start),
+            })
+            .fold(Vec::<Segment>::new(), |mut acc, segment| {
+                match acc.last_mut() {
+                    Some(last) if last.dest().end == segment.dest_start => {
+                        last.src.end = segment.src.end
+                    }
+                    _ => acc.push(segment),
+                }
+                acc
+            });
+
+        PiecewiseMap { segments }
+    }
+
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    fn segment_index(&self, num: u64) -> usize {
+        self.segments.partition_point(|s| s.src.end <= num)
+    }
+
+    pub fn map_value(&self, num: u64) -> u64 {
+        match self.segments.get(self.segment_index(num)) {
+            Some(segment) => segment.apply(num),
+            None => num,
+        }
+    }
+
+    pub fn map_range(&self, range: Range<u64>) -> Vec<Range<u64>> {
+        self.segments[self.segment_index(range.start)..]
+            .iter()
+            .take_while(|s| s.src.start < range.end)
+            .map(|s| {
+                let start = range.start.max(s.src.start);
+                let end = range.end.min(s.src.end);
+                s.apply(start)..s.apply(start) + (end - start)
+            })
+            .collect()
+    }
+
+    // applies `self` first and `next` to its output
+    pub fn compose(&self, next: &PiecewiseMap) -> PiecewiseMap {
+        let starts = self
+            .segments
+            .iter()
+            .flat_map(|s| {
+                let dest = s.dest();
+                let next_starts = next.segments[next.segment_index(dest.start)..]
+                    .iter()
+                    .take_while(move |n| n.src.start < dest.end)
+                    .filter(move |n| n.src.start > dest.start)
+                    .map(move |n| s.src.start + (n.src.start - dest.start));
+                std::iter::once(s.src.start).chain(next_starts)
+            })
+            .collect();
+
+        PiecewiseMap::from_starts(starts, |num| next.map_value(self.map_value(num)))
+    }
+
+    pub fn inverse(&self) -> InversePiecewiseMap {
+        let mut segments = self.segments.clone();
+        segments.sort_by_key(|s| s.dest_start);
+        InversePiecewiseMap { segments }
+    }
+}
+
+impl From<&AlmanacMap> for PiecewiseMap {
+    fn from(map: &AlmanacMap) -> Self {
+        let mut starts = std::iter::once(0)
+            .chain(map.map.iter().flat_map(|(src, _)| [src.start, src.end]))
+            .filter(|v| *v < u64::MAX)
+            .collect::<Vec<_>>();
+        starts.sort();
+        starts.dedup();
+
+        PiecewiseMap::from_starts(starts, |num| map.map_value(num))
+    }
+}
+
+// several sources can land on the same destination, so the inverse gives back all of them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InversePiecewiseMap {
+    segments: Vec<Segment>,
+}
+
+impl InversePiecewiseMap {
+    // sorted by where each segment lands rather than where it starts
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    pub fn preimage_range(&self, range: Range<u64>) -> Vec<Range<u64>> {
+        let candidates = self.segments.partition_point(|s| s.dest_start < range.end);
+        let mut preimage = self.segments[..candidates]
+            .iter()
+            .filter_map(|s| {
+                let dest = s.dest();
+                let start = range.start.max(dest.start);
+                let end = range.end.min(dest.end);
+                match start < end {
+                    true => {
+                        Some(s.src.start + (start - dest.start)..s.src.start + (end - dest.start))
+                    }
+                    false => None,
+                }
+            })
+            .collect::<Vec<_>>();
+        preimage.sort_by_key(|r| r.start);
+        preimage
+    }
+
+    pub fn preimage_value(&self, num: u64) -> Vec<u64> {
+        self.preimage_range(num..num + 1)
+            .into_iter()
+            .map(|r| r.start)
+            .collect()
+    }
+}
+
+pub fn compose_chain(
+    maps: &HashMap<String, AlmanacMap>,
+    from: &str,
+    to: &str,
+) -> anyhow::Result<PiecewiseMap> {
+    let path = resolve_path(maps, from, to)?;
+    Ok(path.into_iter().fold(PiecewiseMap::identity(), |acc, map| {
+        acc.compose(&PiecewiseMap::from(map))
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::part1::{parse_almanac, parse_almanac_map, SeedMode};
+
+    const INPUT: &str = "seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4
+
+water-to-light map:
+88 18 7
+18 25 70
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
+
+temperature-to-humidity map:
+0 69 1
+1 0 69
+
+humidity-to-location map:
+60 56 37
+56 93 4";
+
+    #[test]
+    fn test_from_almanac_map() -> anyhow::Result<()> {
+        let (_, map) = parse_almanac_map(
+            "seed-to-soil map:
+50 98 2
+52 50 48",
+        )?;
+        let piecewise = PiecewiseMap::from(&map);
+        assert_eq!(
+            &[
+                Segment {
+                    src: 0..50,
+                    dest_start: 0
+                },
+                Segment {
+                    src: 50..98,
+                    dest_start: 52
+                },
+                Segment {
+                    src: 98..100,
+                    dest_start: 50
+                },
+                Segment {
+                    src: 100..u64::MAX,
+                    dest_start: 100
+                },
+            ],
+            piecewise.segments()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_compose_chain() -> anyhow::Result<()> {
+        let almanac = parse_almanac(INPUT, SeedMode::Values)?;
+        let chain = compose_chain(&almanac.maps, "seed", "location")?;
+
+        assert_eq!(
+            vec![82, 43, 86, 35],
+            almanac
+                .seed_values()
+                .iter()
+                .map(|s| chain.map_value(*s))
+                .collect::<Vec<_>>()
+        );
+
+        let lowest = [79..93, 55..68]
+            .into_iter()
+            .flat_map(|r| chain.map_range(r))
+            .map(|r| r.start)
+            .min();
+        assert_eq!(Some(46), lowest);
+
+        assert!(compose_chain(&almanac.maps, "seed", "nowhere").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_inverse() -> anyhow::Result<()> {
+        let almanac = parse_almanac(INPUT, SeedMode::Values)?;
+        let chain = compose_chain(&almanac.maps, "seed", "location")?;
+        let inverse = chain.inverse();
+
+        assert!(inverse.preimage_value(46).contains(&82));
+        assert!(inverse.preimage_value(82).contains(&79));
+        for seed in inverse.preimage_range(0..50).into_iter().flatten() {
+            assert!(chain.map_value(seed) < 50);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_inverse_single_map() -> anyhow::Result<()> {
+        let (_, map) = parse_almanac_map(
+            "seed-to-soil map:
+50 98 2
+52 50 48",
+        )?;
+        let inverse = PiecewiseMap::from(&map).inverse();
+        assert_eq!(vec![79], inverse.preimage_value(81));
+        assert_eq!(vec![14], inverse.preimage_value(14));
+        assert_eq!(vec![98], inverse.preimage_value(50));
+        assert_eq!(vec![96], inverse.preimage_value(98));
+
+        // a mapping that lands on a stretch that is also passed through unmapped
+        let (_, map) = parse_almanac_map(
+            "soil-to-fertilizer map:
+10 60 5
+0 5 5",
+        )?;
+        let inverse = PiecewiseMap::from(&map).inverse();
+        assert_eq!(vec![10, 60], inverse.preimage_value(10));
+        assert_eq!(Vec::<u64>::new(), inverse.preimage_value(7));
+        Ok(())
+    }
+
+    proptest! {
+        #[test]
+        fn test_composed_matches_stages(
+            stages in prop::collection::vec(
+                prop::collection::vec((0u64..200, 0u64..200, 1u64..60), 0..6),
+                1..5,
+            ),
+            values in prop::collection::vec(0u64..300, 1..20),
+        ) {
+            let maps = stages
+                .into_iter()
+                .map(|entries| AlmanacMap {
+                    from: String::new(),
+                    to: String::new(),
+                    map: entries
+                        .into_iter()
+                        .map(|(dest, src, len)| (src..src + len, dest..dest + len))
+                        .collect(),
+                })
+                .collect::<Vec<_>>();
+            let composed = maps
+                .iter()
+                .fold(PiecewiseMap::identity(), |acc, m| acc.compose(&PiecewiseMap::from(m)));
+            let inverse = composed.inverse();
+
+            for value in values {
+                let expected = maps.iter().fold(value, |acc, m| m.map_value(acc));
+                prop_assert_eq!(expected, composed.map_value(value));
+                prop_assert!(inverse.preimage_value(expected).contains(&value));
+            }
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Range,
+};
+
+use anyhow::anyhow;
+
+use crate::part1::AlmanacMap;
+
+pub trait Mappable: Sized {
+    fn map_through(self, map: &AlmanacMap) -> Self;
+}
+
+impl Mappable for Vec<u64> {
+    fn map_through(self, map: &AlmanacMap) -> Self {
+        self.into_iter().map(|num| map.map_value(num)).collect()
+    }
+}
+
+impl Mappable for Vec<Range<u64>> {
+    fn map_through(self, map: &AlmanacMap) -> Self {
+        self.into_iter()
+            .flat_map(|range| map.map_range(range))
+            .collect()
+    }
+}
+
+pub fn resolve_path<'a>(
+    maps: &'a HashMap<String, AlmanacMap>,
+    from: &str,
+    to: &str,
+) -> anyhow::Result<Vec<&'a AlmanacMap>> {
+    let mut path = Vec::new();
+    let mut visited = HashSet::new();
+    let mut current = from;
+
+    while current != to {
+        if !visited.insert(current) {
+            return Err(anyhow!(
+                "Maps loop back to {:?} on the way from {:?} to {:?}",
+                current,
+                from,
+                to
+            ));
+        }
+        let map = maps.get(current).ok_or_else(|| {
+            anyhow!(
+                "No map from {:?} on the way from {:?} to {:?}",
+                current,
+                from,
+                to
+            )
+        })?;
+        tracing::debug!("from/to {} {}", map.from, map.to);
+        path.push(map);
+        current = &map.to;
+    }
+
+    Ok(path)
+}
+
+pub fn map_between<T: Mappable>(
+    maps: &HashMap<String, AlmanacMap>,
+    from: &str,
+    to: &str,
+    values: T,
+) -> anyhow::Result<T> {
+    let path = resolve_path(maps, from, to)?;
+    Ok(path
+        .into_iter()
+        .fold(values, |acc, map| acc.map_through(map)))
+}
+
+// every category has at most one outgoing map, so following the maps from any category
+// either runs out or ends up going round in a cycle
+pub fn find_cycles(maps: &HashMap<String, AlmanacMap>) -> Vec<Vec<String>> {
+    let mut done: HashSet<&str> = HashSet::new();
+    let mut cycles = Vec::new();
+
+    let mut starts = maps.keys().map(|k| k.as_str()).collect::<Vec<_>>();
+    starts.sort();
+
+    for start in starts {
+        let mut walk: Vec<&str> = Vec::new();
+        let mut current = start;
+
+        while !done.contains(current) {
+            if let Some(pos) = walk.iter().position(|c| *c == current) {
+                cycles.push(walk[pos..].iter().map(|c| c.to_string()).collect());
+                break;
+            }
+            walk.push(current);
+            match maps.get(current) {
+                Some(map) => current = &map.to,
+                None => break,
+            }
+        }
+
+        done.extend(walk);
+    }
+
+    cycles
+}
+
+pub fn validate_maps(maps: &HashMap<String, AlmanacMap>) -> anyhow::Result<()> {
+    match find_cycles(maps).first() {
+        Some(cycle) => Err(anyhow!("Maps form a cycle: {}", cycle.join(" -> "))),
+        None => Ok(()),
+    }
+}
+
+pub fn collect_almanac_maps(maps: Vec<AlmanacMap>) -> anyhow::Result<HashMap<String, AlmanacMap>> {
+    let mut collected = HashMap::new();
+    for map in maps {
+        if let Some(existing) = collected.insert(map.from.to_string(), map) {
+            return Err(anyhow!(
+                "More than one map from {:?}, found another one to {:?}",
+                existing.from,
+                existing.to
+            ));
+        }
+    }
+
+    Ok(collected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::part1::parse_almanac_map;
+
+    fn maps(input: &str) -> HashMap<String, AlmanacMap> {
+        input
+            .split("\n\n")
+            .map(|m| parse_almanac_map(m).unwrap().1)
+            .map(|m| (m.from.to_string(), m))
+            .collect()
+    }
+
+    const MAPS: &str = "seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4";
+
+    #[test]
+    fn test_map_between() -> anyhow::Result<()> {
+        let maps = maps(MAPS);
+
+        assert_eq!(
+            vec![81, 53, 57, 52],
+            map_between(&maps, "seed", "fertilizer", vec![79, 14, 55, 13])?
+        );
+        assert_eq!(
+            vec![81, 49, 53, 41],
+            map_between(&maps, "seed", "water", vec![79, 14, 55, 13])?
+        );
+        assert_eq!(
+            vec![57..70, 39..44],
+            map_between(&maps, "soil", "fertilizer", vec![57..70, 0..5])?
+        );
+        assert_eq!(vec![1, 2], map_between(&maps, "soil", "soil", vec![1, 2])?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_map_between_missing_link() {
+        let maps = maps(MAPS);
+        assert!(map_between(&maps, "seed", "location", vec![79]).is_err());
+        assert!(map_between(&maps, "water", "seed", vec![79]).is_err());
+    }
+
+    #[test]
+    fn test_cycles() {
+        let maps = maps(
+            "seed-to-soil map:
+1 2 3
+
+soil-to-water map:
+1 2 3
+
+water-to-soil map:
+1 2 3",
+        );
+        assert_eq!(
+            vec![vec!["soil".to_string(), "water".to_string()]],
+            find_cycles(&maps)
+        );
+        assert!(validate_maps(&maps).is_err());
+        assert!(map_between(&maps, "seed", "location", vec![1]).is_err());
+        assert!(validate_maps(&self::maps(MAPS)).is_ok());
+    }
+
+    #[test]
+    fn test_collect_almanac_maps_duplicates() -> anyhow::Result<()> {
+        let (_, first) = parse_almanac_map("seed-to-soil map:\n1 2 3")?;
+        let (_, second) = parse_almanac_map("seed-to-water map:\n1 2 3")?;
+        assert!(collect_almanac_maps(vec![first, second]).is_err());
+        Ok(())
+    }
+}
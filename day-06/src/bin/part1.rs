@@ -1,11 +1,17 @@
-use day_06::part1::solve;
+use day_06::{
+    part1::{parse_racesheets, solve},
+    report::race_report,
+};
 
 #[tracing::instrument]
 fn main() -> anyhow::Result<()> {
     tracing_subscriber::fmt::init();
 
     let file = include_str!("../../input1.txt");
+    if std::env::args().any(|arg| arg == "--report") {
+        println!("{}", race_report(&parse_racesheets(file)?)?);
+    }
     let result = solve(file)?;
     println!("SOLUTION: {}", result);
     Ok(())
-}
\ No newline at end of file
+}
@@ -1,11 +1,17 @@
-use day_06::part2::solve;
+use day_06::{
+    part2::{parse_racesheet, solve},
+    report::race_report,
+};
 
 #[tracing::instrument]
 fn main() -> anyhow::Result<()> {
     tracing_subscriber::fmt::init();
 
     let file = include_str!("../../input2.txt");
+    if std::env::args().any(|arg| arg == "--report") {
+        println!("{}", race_report(&[parse_racesheet(file)?])?);
+    }
     let result = solve(file)?;
     println!("SOLUTION: {}", result);
     Ok(())
-}
\ No newline at end of file
+}
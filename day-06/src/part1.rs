@@ -1,61 +1,191 @@
+use std::ops::RangeInclusive;
+
 use anyhow::anyhow;
-use itertools::Itertools;
 use nom::{
     bytes::complete::tag,
-    character::complete::{newline, space1, u64},
+    character::complete::{digit1, line_ending, multispace0, space1},
     multi::separated_list1,
-    sequence::{preceded, separated_pair, tuple},
+    sequence::{preceded, separated_pair, terminated, tuple},
 };
 
 #[tracing::instrument]
 pub fn solve(input: &str) -> anyhow::Result<String> {
     let race_sheets = parse_racesheets(input)?;
-    let product: u64 = race_sheets
+    let product = race_sheets
         .iter()
         .map(|rs| rs.count_winning_strategies())
-        .product();
+        .try_fold(1u128, |acc, count| acc.checked_mul(count))
+        .ok_or_else(|| anyhow!("Product of the winning strategies does not fit in a u128"))?;
     Ok(product.to_string())
 }
 
+// whether the columns on the sheet are separate races or the digits of one badly kerned race
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kerning {
+    Separate,
+    Joined,
+}
+
 pub fn parse_racesheets(input: &str) -> anyhow::Result<Vec<RaceSheet>> {
+    parse_racesheets_with(input, Kerning::Separate)
+}
+
+pub fn parse_racesheets_with(input: &str, kerning: Kerning) -> anyhow::Result<Vec<RaceSheet>> {
     let time_parser = preceded(
         tuple((tag("Time:"), space1::<&str, nom::error::Error<&str>>)),
-        separated_list1(space1, u64),
+        separated_list1(space1, digit1),
     );
     let distance_parser = preceded(
         tuple((tag("Distance:"), space1)),
-        separated_list1(space1, u64),
+        separated_list1(space1, digit1),
     );
 
-    let (_, (times, distances)) = separated_pair(time_parser, newline, distance_parser)(input)
-        .map_err(|e| anyhow!("Could not parse race sheets: {}", e))?;
+    let (rest, (times, distances)) = terminated(
+        separated_pair(time_parser, line_ending, distance_parser),
+        multispace0,
+    )(input)
+    .map_err(|e| anyhow!("Could not parse race sheets: {}", e))?;
+    if !rest.is_empty() {
+        return Err(anyhow!(
+            "Unexpected input after the race sheets: {:?}",
+            rest
+        ));
+    }
+    if times.len() != distances.len() {
+        return Err(anyhow!(
+            "Found {} times but {} distances",
+            times.len(),
+            distances.len()
+        ));
+    }
 
-    Ok(times
+    let (times, distances): (Vec<String>, Vec<String>) = match kerning {
+        Kerning::Separate => (
+            times.into_iter().map(String::from).collect(),
+            distances.into_iter().map(String::from).collect(),
+        ),
+        Kerning::Joined => (vec![times.concat()], vec![distances.concat()]),
+    };
+
+    times
         .into_iter()
         .zip(distances)
-        .map(|(t, d)| RaceSheet {
-            time: t,
-            distance: d,
+        .map(|(time, distance)| {
+            Ok(RaceSheet {
+                time: time
+                    .parse()
+                    .map_err(|e| anyhow!("Invalid race time {:?}: {}", time, e))?,
+                distance: distance
+                    .parse()
+                    .map_err(|e| anyhow!("Invalid race distance {:?}: {}", distance, e))?,
+            })
         })
-        .collect_vec())
+        .collect()
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct RaceSheet {
-    pub time: u64,
-    pub distance: u64,
+    pub time: u128,
+    pub distance: u128,
 }
 
 impl RaceSheet {
-    pub fn count_winning_strategies(&self) -> u64 {
-        (0..=self.time)
-            .filter(|ms| self.calculate_distance(ms) > self.distance)
-            .count() as u64
+    // the distance is symmetric around half the race time, so everything between the
+    // first winning hold time and its mirror image wins
+    pub fn count_winning_strategies(&self) -> u128 {
+        let first = match self.first_winning_hold() {
+            Some(first) => first,
+            None => return 0,
+        };
+        self.time - 2 * first + 1
+    }
+
+    pub fn winning_hold_times(&self) -> Option<RangeInclusive<u128>> {
+        self.first_winning_hold()
+            .map(|first| first..=self.time - first)
+    }
+
+    // with an odd race time the two middle hold times go equally far, this picks the shorter
+    pub fn optimal_hold_time(&self) -> u128 {
+        self.time / 2
+    }
+
+    pub fn max_distance(&self) -> anyhow::Result<u128> {
+        self.calculate_distance(&self.optimal_hold_time())
+    }
+
+    // how far past the record holding the button for `button_time` goes, if it wins at all
+    pub fn margin_of_victory(&self, button_time: &u128) -> anyhow::Result<Option<u128>> {
+        let distance = self.calculate_distance(button_time)?;
+        Ok(distance
+            .checked_sub(self.distance)
+            .filter(|margin| *margin > 0))
+    }
+
+    pub fn count_winning_strategies_brute_force(&self) -> u128 {
+        (0..=self.time).filter(|ms| self.wins(*ms)).count() as u128
+    }
+
+    // a distance too large for a u128 is always further than the record
+    fn wins(&self, button_time: u128) -> bool {
+        match (self.time - button_time).checked_mul(button_time) {
+            Some(travelled) => travelled > self.distance,
+            None => true,
+        }
+    }
+
+    fn first_winning_hold(&self) -> Option<u128> {
+        if !self.wins(self.time / 2) {
+            return None;
+        }
+        self.first_winning_hold_isqrt()
+            .or_else(|| self.first_winning_hold_binary_search())
+    }
+
+    // the hold times solve h * (t - h) > d, so the first one sits just above the lower
+    // root (t - sqrt(t^2 - 4d)) / 2, the integer root can only be off by a step.
+    // gives up when t^2 or 4d do not fit in a u128
+    fn first_winning_hold_isqrt(&self) -> Option<u128> {
+        let discriminant = self
+            .time
+            .checked_mul(self.time)?
+            .checked_sub(self.distance.checked_mul(4)?)?;
+        let estimate = (self.time - discriminant.isqrt()) / 2;
+
+        (estimate.saturating_sub(1)..=estimate.saturating_add(2).min(self.time / 2))
+            .find(|h| self.wins(*h))
+    }
+
+    // winning is monotonic up to half the race time, so the first winner can be bisected
+    fn first_winning_hold_binary_search(&self) -> Option<u128> {
+        let (mut low, mut high) = (0, self.time / 2);
+        if !self.wins(high) {
+            return None;
+        }
+        while low < high {
+            let mid = low + (high - low) / 2;
+            match self.wins(mid) {
+                true => high = mid,
+                false => low = mid + 1,
+            }
+        }
+        Some(low)
     }
 
-    pub fn calculate_distance(&self, button_time: &u64) -> u64 {
-        let time_to_travel = self.time - button_time;
-        time_to_travel * button_time
+    pub fn calculate_distance(&self, button_time: &u128) -> anyhow::Result<u128> {
+        let time_to_travel = self.time.checked_sub(*button_time).ok_or_else(|| {
+            anyhow!(
+                "Button held for {} but the race is only {} long",
+                button_time,
+                self.time
+            )
+        })?;
+        time_to_travel.checked_mul(*button_time).ok_or_else(|| {
+            anyhow!(
+                "Distance for holding {} does not fit in a u128",
+                button_time
+            )
+        })
     }
 }
 
@@ -97,20 +227,136 @@ Distance:  9  40  200";
         Ok(())
     }
 
+    #[test]
+    fn test_parse_racesheets_with() -> anyhow::Result<()> {
+        let input = "Time:      7  15   30
+Distance:  9  40  200
+";
+        assert_eq!(3, parse_racesheets_with(input, Kerning::Separate)?.len());
+        assert_eq!(
+            vec![RaceSheet {
+                time: 71530,
+                distance: 940200,
+            }],
+            parse_racesheets_with(input, Kerning::Joined)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_racesheets_invalid() {
+        for kerning in [Kerning::Separate, Kerning::Joined] {
+            assert!(
+                parse_racesheets_with("Time:      7  15\nDistance:  9  40  200", kerning).is_err()
+            );
+            assert!(parse_racesheets_with("Time:      7  1a\nDistance:  9  40", kerning).is_err());
+            assert!(parse_racesheets_with("Time:      7  -15\nDistance:  9  40", kerning).is_err());
+            assert!(parse_racesheets_with("Time:      7\nDistance:  9\nTime: 3", kerning).is_err());
+        }
+    }
+
     #[test]
     fn test_calculate_distance() -> anyhow::Result<()> {
         let race_sheet = RaceSheet {
             time: 7,
             distance: 9,
         };
-        assert_eq!(0, race_sheet.calculate_distance(&0));
-        assert_eq!(6, race_sheet.calculate_distance(&1));
-        assert_eq!(10, race_sheet.calculate_distance(&2));
-        assert_eq!(12, race_sheet.calculate_distance(&3));
-        assert_eq!(12, race_sheet.calculate_distance(&4));
-        assert_eq!(10, race_sheet.calculate_distance(&5));
-        assert_eq!(6, race_sheet.calculate_distance(&6));
-        assert_eq!(0, race_sheet.calculate_distance(&7));
+        assert_eq!(0, race_sheet.calculate_distance(&0)?);
+        assert_eq!(6, race_sheet.calculate_distance(&1)?);
+        assert_eq!(10, race_sheet.calculate_distance(&2)?);
+        assert_eq!(12, race_sheet.calculate_distance(&3)?);
+        assert_eq!(12, race_sheet.calculate_distance(&4)?);
+        assert_eq!(10, race_sheet.calculate_distance(&5)?);
+        assert_eq!(6, race_sheet.calculate_distance(&6)?);
+        assert_eq!(0, race_sheet.calculate_distance(&7)?);
+        assert!(race_sheet.calculate_distance(&8).is_err());
+
+        let race_sheet = RaceSheet {
+            time: u128::MAX,
+            distance: 0,
+        };
+        assert!(race_sheet.calculate_distance(&(u128::MAX / 2)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_winning_hold_times() -> anyhow::Result<()> {
+        let race_sheet = RaceSheet {
+            time: 7,
+            distance: 9,
+        };
+        assert_eq!(Some(2..=5), race_sheet.winning_hold_times());
+        assert_eq!(3, race_sheet.optimal_hold_time());
+        assert_eq!(12, race_sheet.max_distance()?);
+        assert_eq!(Some(3), race_sheet.margin_of_victory(&3)?);
+        assert_eq!(Some(1), race_sheet.margin_of_victory(&2)?);
+        assert_eq!(None, race_sheet.margin_of_victory(&1)?);
+        assert!(race_sheet.margin_of_victory(&8).is_err());
+
+        let race_sheet = RaceSheet {
+            time: 30,
+            distance: 225,
+        };
+        assert_eq!(None, race_sheet.winning_hold_times());
+        assert_eq!(225, race_sheet.max_distance()?);
         Ok(())
     }
+
+    #[test]
+    fn test_count_winning_strategies_matches_brute_force() {
+        for time in 0..=80 {
+            for distance in 0..=time * time / 4 + 2 {
+                let race_sheet = RaceSheet { time, distance };
+                assert_eq!(
+                    race_sheet.count_winning_strategies_brute_force(),
+                    race_sheet.count_winning_strategies(),
+                    "{:?}",
+                    race_sheet
+                );
+                assert_eq!(
+                    race_sheet.first_winning_hold_isqrt(),
+                    race_sheet.first_winning_hold_binary_search(),
+                    "{:?}",
+                    race_sheet
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_count_winning_strategies_large() {
+        let race_sheet = RaceSheet {
+            time: 71530,
+            distance: 940200,
+        };
+        assert_eq!(71503, race_sheet.count_winning_strategies());
+
+        let race_sheet = RaceSheet {
+            time: u32::MAX as u128,
+            distance: 0,
+        };
+        assert_eq!(u32::MAX as u128 - 1, race_sheet.count_winning_strategies());
+
+        let race_sheet = RaceSheet {
+            time: 1 << 100,
+            distance: 1 << 90,
+        };
+        assert_eq!(
+            race_sheet.first_winning_hold_binary_search(),
+            race_sheet.first_winning_hold()
+        );
+        assert!(race_sheet.first_winning_hold_isqrt().is_none());
+        assert_eq!((1 << 100) - 1, race_sheet.count_winning_strategies());
+    }
+
+    #[test]
+    fn test_solve_overflow() {
+        let input = "Time:      340282366920938463463374607431768211456
+Distance:  9";
+        assert!(solve(input).is_err());
+
+        let input = "Time:      18446744073709551616 18446744073709551616 18446744073709551616
+Distance:  0 0 0";
+        assert!(solve(input).is_err());
+    }
 }
@@ -1,13 +1,6 @@
 use anyhow::anyhow;
-use nom::{
-    bytes::complete::tag,
-    character::complete::{alphanumeric1, newline, space1},
-    combinator::map,
-    multi::separated_list1,
-    sequence::{preceded, separated_pair, tuple},
-};
 
-use crate::part1::RaceSheet;
+use crate::part1::{parse_racesheets_with, Kerning, RaceSheet};
 
 #[tracing::instrument]
 pub fn solve(input: &str) -> anyhow::Result<String> {
@@ -16,23 +9,9 @@ pub fn solve(input: &str) -> anyhow::Result<String> {
 }
 
 pub fn parse_racesheet(input: &str) -> anyhow::Result<RaceSheet> {
-    let time_parser = preceded(
-        tuple((tag("Time:"), space1::<&str, nom::error::Error<&str>>)),
-        map(separated_list1(space1, alphanumeric1), |v| {
-            v.join("").parse::<u64>().unwrap()
-        }),
-    );
-    let distance_parser = preceded(
-        tuple((tag("Distance:"), space1::<&str, nom::error::Error<&str>>)),
-        map(separated_list1(space1, alphanumeric1), |v| {
-            v.join("").parse::<u64>().unwrap()
-        }),
-    );
-
-    let (_, (time, distance)) = separated_pair(time_parser, newline, distance_parser)(input)
-        .map_err(|e| anyhow!("Could not parse race sheets: {}", e))?;
-
-    Ok(RaceSheet { time, distance })
+    parse_racesheets_with(input, Kerning::Joined)?
+        .pop()
+        .ok_or_else(|| anyhow!("No race on the sheet"))
 }
 
 #[cfg(test)]
@@ -46,4 +25,27 @@ Distance:  940200";
         assert_eq!("71503", solve(input)?);
         Ok(())
     }
+
+    #[test]
+    fn test_solve_wide() -> anyhow::Result<()> {
+        let input = "Time:      7153000000  0000000000
+Distance:  9402000000  0000000000";
+        assert_eq!(
+            RaceSheet {
+                time: 71530000000000000000,
+                distance: 94020000000000000000,
+            },
+            parse_racesheet(input)?
+        );
+        assert_eq!("71529999999999999997", solve(input)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_racesheet_out_of_range() {
+        let input = "Time:      34028236692093846346 3374607431768211456
+Distance:  940200";
+        assert!(parse_racesheet(input).is_err());
+        assert!(parse_racesheet("Time:      7x\nDistance:  9").is_err());
+    }
 }
@@ -0,0 +1,352 @@
+use crate::part1::RaceSheet;
+
+pub trait PhysicsModel {
+    // how far the boat goes after holding the button for `button_time` of the race,
+    // `None` when the button is held past the end or the distance does not fit in a u128
+    fn distance(&self, button_time: u128, race_time: u128) -> Option<u128>;
+
+    // models that can count their winning hold times directly return them here,
+    // everything else is searched hold time by hold time
+    fn closed_form_count(&self, _race_sheet: &RaceSheet) -> Option<u128> {
+        None
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoatPhysics {
+    pub acceleration: u128,
+    pub max_speed: Option<u128>,
+    pub starting_speed: u128,
+    pub drag: u128,
+}
+
+// the boat from the puzzle, every ms held adds 1 mm/ms of speed and nothing slows it down
+impl Default for BoatPhysics {
+    fn default() -> Self {
+        BoatPhysics {
+            acceleration: 1,
+            max_speed: None,
+            starting_speed: 0,
+            drag: 0,
+        }
+    }
+}
+
+impl BoatPhysics {
+    pub fn speed(&self, button_time: u128) -> Option<u128> {
+        let speed = self
+            .acceleration
+            .checked_mul(button_time)?
+            .checked_add(self.starting_speed)?;
+        Some(match self.max_speed {
+            Some(max_speed) => speed.min(max_speed),
+            None => speed,
+        })
+    }
+}
+
+impl PhysicsModel for BoatPhysics {
+    // with drag the boat loses `drag` mm/ms of speed every ms until it stops,
+    // so it covers an arithmetic series for as many ms as it keeps moving
+    fn distance(&self, button_time: u128, race_time: u128) -> Option<u128> {
+        let speed = self.speed(button_time)?;
+        let time_to_travel = race_time.checked_sub(button_time)?;
+        if self.drag == 0 {
+            return speed.checked_mul(time_to_travel);
+        }
+
+        let moving = time_to_travel.min(speed.div_ceil(self.drag));
+        let slowdown = match moving % 2 {
+            0 => (moving / 2).checked_mul(moving.saturating_sub(1))?,
+            _ => moving.checked_mul(moving.saturating_sub(1) / 2)?,
+        };
+        moving
+            .checked_mul(speed)?
+            .checked_sub(self.drag.checked_mul(slowdown)?)
+    }
+
+    // without a speed cap or drag the distance is the parabola (a * h + s) * (t - h), so the
+    // winning hold times sit between its roots. the integer roots are at most a step away
+    // from the isqrt estimates, anything that overflows or misses is left to the search
+    fn closed_form_count(&self, race_sheet: &RaceSheet) -> Option<u128> {
+        if self.max_speed.is_some() || self.drag != 0 || self.acceleration == 0 {
+            return None;
+        }
+        if *self == BoatPhysics::default() {
+            return Some(race_sheet.count_winning_strategies());
+        }
+
+        let a = i128::try_from(self.acceleration).ok()?;
+        let s = i128::try_from(self.starting_speed).ok()?;
+        let t = i128::try_from(race_sheet.time).ok()?;
+        let d = i128::try_from(race_sheet.distance).ok()?;
+
+        // -a * h^2 + b * h + c > 0
+        let b = a.checked_mul(t)?.checked_sub(s)?;
+        let c = s.checked_mul(t)?.checked_sub(d)?;
+        let discriminant = b
+            .checked_mul(b)?
+            .checked_add(a.checked_mul(4)?.checked_mul(c)?)?;
+        if discriminant < 0 {
+            return Some(0);
+        }
+        let root = discriminant.isqrt();
+        let two_a = a.checked_mul(2)?;
+
+        let wins = |h: i128| {
+            (0..=t).contains(&h)
+                && match self.distance(h as u128, race_sheet.time) {
+                    Some(distance) => distance > race_sheet.distance,
+                    None => true,
+                }
+        };
+        let first_estimate = (b - root).div_euclid(two_a);
+        let last_estimate = (b + root).div_euclid(two_a);
+        let first = (first_estimate - 1..=first_estimate + 2).find(|h| wins(*h) && !wins(h - 1))?;
+        let last = (last_estimate - 1..=last_estimate + 2).find(|h| wins(*h) && !wins(h + 1))?;
+        Some((last - first + 1) as u128)
+    }
+}
+
+// the first value in `low..=high` where `pred` stops holding, `pred` has to hold for some
+// stretch at the start of the range and never again after it
+fn bisect(mut low: u128, mut high: u128, pred: impl Fn(u128) -> bool) -> Option<u128> {
+    if pred(high) {
+        return None;
+    }
+    while low < high {
+        let mid = low + (high - low) / 2;
+        match pred(mid) {
+            true => low = mid + 1,
+            false => high = mid,
+        }
+    }
+    Some(low)
+}
+
+impl RaceSheet {
+    // holding longer only ever makes the boat faster but leaves it less time, so the
+    // distance climbs to a single peak and falls away after it. both edges of the winning
+    // hold times can then be bisected on either side of the peak
+    pub fn count_winning_strategies_with<M: PhysicsModel>(&self, model: &M) -> u128 {
+        if let Some(count) = model.closed_form_count(self) {
+            return count;
+        }
+
+        // a distance too large for a u128 is always further than the record
+        let distance = |button_time| model.distance(button_time, self.time).unwrap_or(u128::MAX);
+        let peak = bisect(0, self.time, |h| {
+            h < self.time && distance(h + 1) > distance(h)
+        })
+        .unwrap_or(self.time);
+        if distance(peak) <= self.distance {
+            return 0;
+        }
+
+        let first = bisect(0, peak, |h| distance(h) <= self.distance).unwrap_or(peak);
+        let last = bisect(peak, self.time, |h| distance(h) > self.distance)
+            .map(|h| h - 1)
+            .unwrap_or(self.time);
+        last - first + 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::part1::parse_racesheets;
+
+    // moves the boat one ms at a time
+    fn simulate(physics: &BoatPhysics, button_time: u128, race_time: u128) -> u128 {
+        let mut speed = physics.speed(button_time).unwrap();
+        let mut distance = 0;
+        for _ in button_time..race_time {
+            distance += speed;
+            speed = speed.saturating_sub(physics.drag);
+        }
+        distance
+    }
+
+    #[test]
+    fn test_default_physics() -> anyhow::Result<()> {
+        let physics = BoatPhysics::default();
+        for race_sheet in parse_racesheets("Time:      7  15   30\nDistance:  9  40  200")? {
+            assert_eq!(
+                race_sheet.count_winning_strategies(),
+                race_sheet.count_winning_strategies_with(&physics)
+            );
+            for button_time in 0..=race_sheet.time {
+                assert_eq!(
+                    Some(race_sheet.calculate_distance(&button_time)?),
+                    physics.distance(button_time, race_sheet.time)
+                );
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_distance_matches_simulation() {
+        let models = [
+            BoatPhysics {
+                acceleration: 2,
+                ..Default::default()
+            },
+            BoatPhysics {
+                max_speed: Some(4),
+                starting_speed: 1,
+                ..Default::default()
+            },
+            BoatPhysics {
+                drag: 2,
+                ..Default::default()
+            },
+            BoatPhysics {
+                acceleration: 3,
+                max_speed: Some(10),
+                starting_speed: 2,
+                drag: 3,
+            },
+        ];
+        for physics in models {
+            for race_time in 0..30 {
+                for button_time in 0..=race_time {
+                    assert_eq!(
+                        Some(simulate(&physics, button_time, race_time)),
+                        physics.distance(button_time, race_time),
+                        "{:?} holding {} of {}",
+                        physics,
+                        button_time,
+                        race_time
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_count_winning_strategies_with() {
+        let race_sheet = RaceSheet {
+            time: 7,
+            distance: 9,
+        };
+        let capped = BoatPhysics {
+            max_speed: Some(2),
+            ..Default::default()
+        };
+        // only holding for 2 ms reaches top speed with enough time left, 2 * 5 = 10
+        assert_eq!(1, race_sheet.count_winning_strategies_with(&capped));
+
+        let draggy = BoatPhysics {
+            drag: 1,
+            ..Default::default()
+        };
+        // holding 4 ms goes 4 + 3 + 2 = 9, holding 5 ms goes 5 + 4 = 9, neither beats it
+        assert_eq!(0, race_sheet.count_winning_strategies_with(&draggy));
+    }
+
+    // checks every hold time one by one
+    fn scan(physics: &BoatPhysics, race_sheet: &RaceSheet) -> u128 {
+        (0..=race_sheet.time)
+            .filter(|h| physics.distance(*h, race_sheet.time).unwrap() > race_sheet.distance)
+            .count() as u128
+    }
+
+    #[test]
+    fn test_count_matches_scan() {
+        let models = [
+            BoatPhysics {
+                acceleration: 2,
+                starting_speed: 3,
+                ..Default::default()
+            },
+            BoatPhysics {
+                acceleration: 0,
+                starting_speed: 5,
+                ..Default::default()
+            },
+            BoatPhysics {
+                max_speed: Some(6),
+                starting_speed: 1,
+                ..Default::default()
+            },
+            BoatPhysics {
+                drag: 2,
+                ..Default::default()
+            },
+            BoatPhysics {
+                acceleration: 3,
+                max_speed: Some(10),
+                starting_speed: 2,
+                drag: 3,
+            },
+        ];
+        for physics in models {
+            for time in 0..40 {
+                for distance in (0..300).step_by(7) {
+                    let race_sheet = RaceSheet { time, distance };
+                    assert_eq!(
+                        scan(&physics, &race_sheet),
+                        race_sheet.count_winning_strategies_with(&physics),
+                        "{:?} on {:?}",
+                        physics,
+                        race_sheet
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_count_wide_races() {
+        let physics = BoatPhysics {
+            acceleration: 2,
+            starting_speed: 1,
+            ..Default::default()
+        };
+        // a cap no boat can reach takes the search instead of the closed form
+        let uncapped = BoatPhysics {
+            max_speed: Some(u128::MAX),
+            ..physics.clone()
+        };
+
+        let race_sheet = RaceSheet {
+            time: 10u128.pow(18),
+            distance: 4 * 10u128.pow(35),
+        };
+        assert!(physics.closed_form_count(&race_sheet).is_some());
+        assert_eq!(
+            447213595499957940,
+            race_sheet.count_winning_strategies_with(&physics)
+        );
+        assert_eq!(
+            447213595499957940,
+            race_sheet.count_winning_strategies_with(&uncapped)
+        );
+
+        // too wide for the closed form to square
+        let race_sheet = RaceSheet {
+            time: 10u128.pow(19),
+            distance: 4 * 10u128.pow(37),
+        };
+        assert_eq!(
+            4472135954999579394,
+            race_sheet.count_winning_strategies_with(&physics)
+        );
+        assert_eq!(
+            4472135954999579394,
+            race_sheet.count_winning_strategies_with(&uncapped)
+        );
+
+        let race_sheet = RaceSheet {
+            time: 71530000000000000000,
+            distance: 94020000000000000000,
+        };
+        let draggy = BoatPhysics {
+            max_speed: Some(1_000_000),
+            drag: 1,
+            ..Default::default()
+        };
+        assert_eq!(0, race_sheet.count_winning_strategies_with(&draggy));
+    }
+}
@@ -0,0 +1,55 @@
+use crate::part1::RaceSheet;
+
+pub fn race_report(race_sheets: &[RaceSheet]) -> anyhow::Result<String> {
+    race_sheets
+        .iter()
+        .enumerate()
+        .map(|(i, race_sheet)| {
+            let optimal = race_sheet.optimal_hold_time();
+            let max_distance = race_sheet.max_distance()?;
+            let header = format!(
+                "Race {}: time {}, record {}, best hold {} goes {}",
+                i + 1,
+                race_sheet.time,
+                race_sheet.distance,
+                optimal,
+                max_distance
+            );
+
+            Ok(match race_sheet.winning_hold_times() {
+                Some(range) => format!(
+                    "{}\n  wins holding {}..={} ({} ways), by up to {}",
+                    header,
+                    range.start(),
+                    range.end(),
+                    race_sheet.count_winning_strategies(),
+                    max_distance - race_sheet.distance
+                ),
+                None => format!("{}\n  no hold time beats the record", header),
+            })
+        })
+        .collect::<anyhow::Result<Vec<_>>>()
+        .map(|lines| lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::part1::parse_racesheets;
+
+    #[test]
+    fn test_race_report() -> anyhow::Result<()> {
+        let race_sheets = parse_racesheets(
+            "Time:      7  30
+Distance:  9  225",
+        )?;
+        assert_eq!(
+            "Race 1: time 7, record 9, best hold 3 goes 12
+  wins holding 2..=5 (4 ways), by up to 3
+Race 2: time 30, record 225, best hold 15 goes 225
+  no hold time beats the record",
+            race_report(&race_sheets)?
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,62 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use day_07::engine::{parse_game, JokersWild, RuleSet};
+
+// a deterministic spread of hands so every run sorts the same game
+fn game(hands: usize) -> String {
+    const CARDS: &[u8] = b"23456789TJQKA";
+    let mut seed = 0x2545_f491_4f6c_dd1d_u64;
+    (0..hands)
+        .map(|bid| {
+            let cards = (0..5)
+                .map(|_| {
+                    seed ^= seed << 13;
+                    seed ^= seed >> 7;
+                    seed ^= seed << 17;
+                    CARDS[(seed % CARDS.len() as u64) as usize] as char
+                })
+                .collect::<String>();
+            format!("{} {}", cards, bid + 1)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn sort(c: &mut Criterion) {
+    let input = game(10_000);
+    let mut group = c.benchmark_group("sort 10k hands");
+
+    group.bench_function("cached sort key", |b| {
+        b.iter_batched(
+            || parse_game::<JokersWild>(&input).unwrap(),
+            |mut hands| hands.sort(),
+            BatchSize::LargeInput,
+        )
+    });
+
+    // how sorting worked before the key was cached, the hand type is worked out again for
+    // both sides of every comparison
+    group.bench_function("hand type per comparison", |b| {
+        b.iter_batched(
+            || parse_game::<JokersWild>(&input).unwrap(),
+            |mut hands| {
+                hands.sort_by(|a, b| {
+                    JokersWild::hand_type(&a.cards.0, &a.suits)
+                        .cmp(&JokersWild::hand_type(&b.cards.0, &b.suits))
+                        .then_with(|| {
+                            a.cards
+                                .0
+                                .iter()
+                                .map(JokersWild::card_rank)
+                                .cmp(b.cards.0.iter().map(JokersWild::card_rank))
+                        })
+                })
+            },
+            BatchSize::LargeInput,
+        )
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, sort);
+criterion_main!(benches);
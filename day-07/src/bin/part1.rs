@@ -1,11 +1,22 @@
-use day_07::part1::solve;
+use day_07::{
+    engine::Standard,
+    part1::solve,
+    report::{format_json, format_table, ranking_report},
+};
 
 #[tracing::instrument]
 fn main() -> anyhow::Result<()> {
     tracing_subscriber::fmt::init();
 
     let file = include_str!("../../input1.txt");
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--table" => println!("{}", format_table(&ranking_report::<Standard>(file)?)),
+            "--json" => println!("{}", format_json(&ranking_report::<Standard>(file)?)?),
+            v => return Err(anyhow::anyhow!("Unknown argument: {:?}", v)),
+        }
+    }
     let result = solve(file)?;
     println!("SOLUTION: {}", result);
     Ok(())
-}
\ No newline at end of file
+}
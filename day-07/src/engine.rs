@@ -0,0 +1,587 @@
+use std::{collections::HashMap, fmt, marker::PhantomData};
+
+use anyhow::anyhow;
+use itertools::Itertools;
+use nom::{
+    character::complete::{anychar, space1, u64},
+    combinator::{all_consuming, map, map_res},
+    multi::count,
+    sequence::{pair, preceded},
+    Finish, IResult,
+};
+use serde::Serialize;
+
+use crate::variants::{Camel, Jokers, NoWilds};
+
+// everything that differs between the ways of playing Camel Cards,
+// the rest of the engine is shared
+pub trait RuleSet: std::fmt::Debug + Eq {
+    // the sort key has a nibble per card after the hand type, so at most `MAX_HAND_SIZE`
+    // cards fit. building a hand for a rule set with more fails to compile
+    const HAND_SIZE: usize = 5;
+
+    // suited cards are written as the card followed by its suit, `Th` is the ten of hearts
+    const SUITED: bool = false;
+
+    fn card(ch: char) -> anyhow::Result<Card> {
+        Card::try_from(ch)
+    }
+
+    // the strength of a card when two hands of the same type are compared, higher wins.
+    // has to fit in 4 bits to be packed into the sort key
+    fn card_rank(card: &Card) -> u8;
+
+    // `suits` is empty unless the rule set is suited
+    fn hand_type(cards: &[Card], suits: &[Suit]) -> HandType;
+
+    // the cards the hand counts as once any wild cards have been swapped for real ones
+    fn played_as(cards: &[Card], _suits: &[Suit]) -> Vec<Card> {
+        cards.to_vec()
+    }
+
+    // the hand type along with a sort key that packs the type followed by a nibble per card
+    // rank, so comparing keys compares the type first and then the cards left to right.
+    // rule sets that work out both in one go can override this to only do it once
+    fn evaluate(cards: &[Card], suits: &[Suit]) -> (HandType, u64) {
+        let hand_type = Self::hand_type(cards, suits);
+        (
+            hand_type,
+            pack_sort_key(hand_type, cards.iter().map(Self::card_rank)),
+        )
+    }
+}
+
+pub const MAX_HAND_SIZE: usize = 15;
+
+pub fn pack_sort_key(hand_type: HandType, ranks: impl Iterator<Item = u8>) -> u64 {
+    ranks.fold(hand_type as u64, |key, rank| (key << 4) | rank as u64)
+}
+
+// the two rule sets from the puzzle are both Camel Cards, they only differ in their wild cards
+pub type Standard = Camel<NoWilds>;
+
+// `J` is a joker that counts as whichever card makes the best hand, but is the weakest card
+// when breaking ties
+pub type JokersWild = Camel<Jokers>;
+
+// every wild card turns into the card there are the most of, the strongest one on a tie.
+// piling everything onto the biggest group always makes the best hand, since the hand types
+// go up with the size of the biggest group and then the second biggest. a hand of nothing
+// but wild cards turns into aces
+pub fn promote_wild_cards(cards: &[Card], is_wild: impl Fn(&Card) -> bool) -> Vec<Card> {
+    let natural = cards.iter().copied().filter(|c| !is_wild(c)).collect_vec();
+    let target = card_counts(&natural)
+        .into_iter()
+        .max_by_key(|(card, count)| (*count, *card as u8))
+        .map(|(card, _)| card)
+        .unwrap_or(Card::Ace);
+
+    cards
+        .iter()
+        .map(|c| if is_wild(c) { target } else { *c })
+        .collect()
+}
+
+pub fn card_counts(cards: &[Card]) -> HashMap<Card, u8> {
+    cards.iter().fold(HashMap::new(), |mut acc, c| {
+        acc.entry(*c).and_modify(|count| *count += 1u8).or_insert(1);
+        acc
+    })
+}
+
+pub fn total_winnings<R: RuleSet>(input: &str) -> anyhow::Result<u64> {
+    let mut hands = parse_game::<R>(input)?;
+    hands.sort();
+    Ok(hands
+        .iter()
+        .enumerate()
+        .map(|(i, h)| (i as u64 + 1) * h.bid)
+        .sum())
+}
+
+// the hand type and sort key are worked out once up front, so sorting only compares integers
+#[derive(Debug, PartialEq, Eq)]
+pub struct Hand<R> {
+    pub cards: HandCards,
+    pub suits: Vec<Suit>,
+    pub bid: u64,
+    hand_type: HandType,
+    sort_key: u64,
+    rules: PhantomData<R>,
+}
+
+impl<R: RuleSet> Hand<R> {
+    // only for rule sets without suits, a suited hand has to say what suit every card is
+    pub fn new(cards: HandCards, bid: u64) -> Self {
+        const { assert!(!R::SUITED, "Suited hands are built with Hand::with_suits") };
+        Hand::build(cards, Vec::new(), bid)
+    }
+
+    pub fn with_suits(cards: HandCards, suits: Vec<Suit>, bid: u64) -> anyhow::Result<Self> {
+        match (R::SUITED, suits.len()) {
+            (true, n) if n != cards.0.len() => Err(anyhow!(
+                "Expected a suit for each of the {} cards but found {}",
+                cards.0.len(),
+                n
+            )),
+            (false, 1..) => Err(anyhow!("Cards in this rule set do not have suits")),
+            _ => Ok(Hand::build(cards, suits, bid)),
+        }
+    }
+
+    // the parser always reads a suit for every card when the rule set is suited
+    fn build(cards: HandCards, suits: Vec<Suit>, bid: u64) -> Self {
+        const {
+            assert!(
+                R::HAND_SIZE <= MAX_HAND_SIZE,
+                "The sort key only has room for 15 cards"
+            )
+        };
+        let (hand_type, sort_key) = R::evaluate(&cards.0, &suits);
+        Hand {
+            cards,
+            suits,
+            bid,
+            hand_type,
+            sort_key,
+            rules: PhantomData,
+        }
+    }
+
+    pub fn hand_type(&self) -> HandType {
+        self.hand_type
+    }
+
+    pub fn sort_key(&self) -> u64 {
+        self.sort_key
+    }
+}
+
+impl<R: RuleSet> PartialOrd for Hand<R> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<R: RuleSet> Ord for Hand<R> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.sort_key.cmp(&other.sort_key)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct HandCards(pub Vec<Card>);
+
+// Camel Cards never makes straights or flushes, so slotting them in where poker ranks them
+// leaves the Camel Cards order alone
+#[derive(Clone, Copy, PartialOrd, Ord, PartialEq, Eq, Debug, Serialize)]
+pub enum HandType {
+    HighCard,
+    OnePair,
+    TwoPair,
+    ThreeOfKind,
+    Straight,
+    Flush,
+    FullHouse,
+    FourOfKind,
+    StraightFlush,
+    FiveOfKind,
+}
+
+impl HandType {
+    pub fn from_counts(mut counts: Vec<u8>) -> Self {
+        counts.sort_unstable_by(|a, b| b.cmp(a));
+        match (
+            counts.first().copied().unwrap_or(0),
+            counts.get(1).copied().unwrap_or(0),
+        ) {
+            (5.., _) => HandType::FiveOfKind,
+            (4, _) => HandType::FourOfKind,
+            (3, 2..) => HandType::FullHouse,
+            (3, _) => HandType::ThreeOfKind,
+            (2, 2) => HandType::TwoPair,
+            (2, _) => HandType::OnePair,
+            _ => HandType::HighCard,
+        }
+    }
+}
+
+// only rule sets with jokers turn `J` into a `Joker`, everyone else reads it as a `Jack`
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Card {
+    Joker = 1,
+    Two,
+    Three,
+    Four,
+    Five,
+    Six,
+    Seven,
+    Eight,
+    Nine,
+    Ten,
+    Jack,
+    Queen,
+    King,
+    Ace,
+}
+
+impl Card {
+    // every card apart from the joker, weakest first
+    pub const NATURAL: [Card; 13] = [
+        Card::Two,
+        Card::Three,
+        Card::Four,
+        Card::Five,
+        Card::Six,
+        Card::Seven,
+        Card::Eight,
+        Card::Nine,
+        Card::Ten,
+        Card::Jack,
+        Card::Queen,
+        Card::King,
+        Card::Ace,
+    ];
+
+    pub fn from_rank(rank: u8) -> Option<Card> {
+        match rank {
+            1 => Some(Card::Joker),
+            r => Card::NATURAL.iter().find(|c| **c as u8 == r).copied(),
+        }
+    }
+}
+
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ch = match self {
+            Card::Joker | Card::Jack => 'J',
+            Card::Ace => 'A',
+            Card::King => 'K',
+            Card::Queen => 'Q',
+            Card::Ten => 'T',
+            c => (b'0' + *c as u8) as char,
+        };
+        write!(f, "{}", ch)
+    }
+}
+
+impl TryFrom<char> for Card {
+    type Error = anyhow::Error;
+
+    fn try_from(ch: char) -> Result<Self, Self::Error> {
+        match ch {
+            'A' => Ok(Self::Ace),
+            'K' => Ok(Self::King),
+            'Q' => Ok(Self::Queen),
+            'J' => Ok(Self::Jack),
+            'T' => Ok(Self::Ten),
+            '9' => Ok(Self::Nine),
+            '8' => Ok(Self::Eight),
+            '7' => Ok(Self::Seven),
+            '6' => Ok(Self::Six),
+            '5' => Ok(Self::Five),
+            '4' => Ok(Self::Four),
+            '3' => Ok(Self::Three),
+            '2' => Ok(Self::Two),
+            v => Err(anyhow!("Invalid card: {:?}", v)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Suit {
+    Clubs,
+    Diamonds,
+    Hearts,
+    Spades,
+}
+
+impl fmt::Display for Suit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ch = match self {
+            Suit::Clubs => 'c',
+            Suit::Diamonds => 'd',
+            Suit::Hearts => 'h',
+            Suit::Spades => 's',
+        };
+        write!(f, "{}", ch)
+    }
+}
+
+impl TryFrom<char> for Suit {
+    type Error = anyhow::Error;
+
+    fn try_from(ch: char) -> Result<Self, Self::Error> {
+        match ch {
+            'c' => Ok(Self::Clubs),
+            'd' => Ok(Self::Diamonds),
+            'h' => Ok(Self::Hearts),
+            's' => Ok(Self::Spades),
+            v => Err(anyhow!("Invalid suit: {:?}", v)),
+        }
+    }
+}
+
+pub fn parse_game<R: RuleSet>(input: &str) -> anyhow::Result<Vec<Hand<R>>> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            all_consuming(hand)(line.trim_end())
+                .finish()
+                .map(|(_, hand)| hand)
+                .map_err(|_| {
+                    anyhow!(
+                        "Could not parse hand {:?} on line {}: {}",
+                        line,
+                        i + 1,
+                        hand_error::<R>(line)
+                    )
+                })
+        })
+        .collect()
+}
+
+// nom only tells us where the hand stopped making sense, this works out why
+fn hand_error<R: RuleSet>(line: &str) -> anyhow::Error {
+    let chars = line
+        .split_whitespace()
+        .next()
+        .unwrap_or("")
+        .chars()
+        .collect_vec();
+    let chars_per_card = if R::SUITED { 2 } else { 1 };
+    for card in chars.chunks(chars_per_card) {
+        if let Err(e) = R::card(card[0]) {
+            return e;
+        }
+        if let Some(Err(e)) = card.get(1).map(|ch| Suit::try_from(*ch)) {
+            return e;
+        }
+    }
+    match chars.len().div_ceil(chars_per_card) {
+        n if n == R::HAND_SIZE && chars.len().is_multiple_of(chars_per_card) => {
+            anyhow!("Expected a bid after the cards")
+        }
+        n if n == R::HAND_SIZE => anyhow!("Expected a suit after the last card"),
+        n => anyhow!("Expected {} cards but found {}", R::HAND_SIZE, n),
+    }
+}
+
+pub fn hand<R: RuleSet>(input: &str) -> IResult<&str, Hand<R>> {
+    let (rest, (cards, suits)) = match R::SUITED {
+        true => map(count(pair(card::<R>, suit), R::HAND_SIZE), |cards| {
+            cards.into_iter().unzip()
+        })(input)?,
+        false => map(count(card::<R>, R::HAND_SIZE), |cards| (cards, Vec::new()))(input)?,
+    };
+    let (rest, bid) = preceded(space1, u64)(rest)?;
+    Ok((rest, Hand::build(HandCards(cards), suits, bid)))
+}
+
+pub fn suit(input: &str) -> IResult<&str, Suit> {
+    map_res(anychar, Suit::try_from)(input)
+}
+
+pub fn card<R: RuleSet>(input: &str) -> IResult<&str, Card> {
+    map_res(anychar, R::card)(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::variants::{Camel, JokersAndDeuces, Wilds};
+
+    // aces low, everything else as usual
+    #[derive(Debug, PartialEq, Eq)]
+    struct AcesLow;
+
+    impl RuleSet for AcesLow {
+        fn card_rank(card: &Card) -> u8 {
+            match card {
+                Card::Ace => 1,
+                c => *c as u8,
+            }
+        }
+
+        fn hand_type(cards: &[Card], suits: &[Suit]) -> HandType {
+            Standard::hand_type(cards, suits)
+        }
+    }
+
+    #[test]
+    fn test_custom_rule_set() -> anyhow::Result<()> {
+        let (_, aces) = hand::<AcesLow>("A2345 1")?;
+        let (_, twos) = hand::<AcesLow>("2A345 1")?;
+        assert!(aces < twos);
+
+        let (_, aces) = hand::<Standard>("A2345 1")?;
+        let (_, twos) = hand::<Standard>("2A345 1")?;
+        assert!(aces > twos);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_card() -> anyhow::Result<()> {
+        assert_eq!(("", Card::Jack), card::<Standard>("J")?);
+        assert_eq!(("", Card::Joker), card::<JokersWild>("J")?);
+        assert!(card::<Standard>("X").is_err());
+        assert!(card::<Standard>("").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_display_cards() -> anyhow::Result<()> {
+        for card in "23456789TJQKA".chars() {
+            assert_eq!(card.to_string(), Card::try_from(card)?.to_string());
+        }
+        assert_eq!("J", Card::Joker.to_string());
+        assert_eq!(Some(Card::Ten), Card::from_rank(10));
+        assert_eq!(None, Card::from_rank(15));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_malformed_hands() {
+        for (input, error) in [
+            ("32T3 765", "Expected 5 cards but found 4"),
+            ("32T3KK 765", "Expected 5 cards but found 6"),
+            ("32T3X 765", "Invalid card: 'X'"),
+            ("32t3K 765", "Invalid card: 't'"),
+            ("32T3K", "Expected a bid after the cards"),
+            ("32T3K 76x", "Expected a bid after the cards"),
+            ("32T3K -765", "Expected a bid after the cards"),
+        ] {
+            let e = parse_game::<Standard>(input).unwrap_err().to_string();
+            assert!(e.ends_with(error), "{:?} gave {:?}", input, e);
+        }
+
+        let e = parse_game::<Standard>("32T3K 765\nKK677 28\nKTJJ 220")
+            .unwrap_err()
+            .to_string();
+        assert!(e.contains("line 3"), "{}", e);
+    }
+
+    #[test]
+    fn test_parse_game_trailing_newline() -> anyhow::Result<()> {
+        assert_eq!(2, parse_game::<Standard>("32T3K 765\nKK677 28\n")?.len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sort_key() -> anyhow::Result<()> {
+        let (_, standard) = hand::<Standard>("T55J5 684")?;
+        assert_eq!(0x3a55b5, standard.sort_key());
+        let (_, jokers) = hand::<JokersWild>("T55J5 684")?;
+        assert_eq!(0x7a5515, jokers.sort_key());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sort_key_matches_card_order() -> anyhow::Result<()> {
+        let input = "32T3K 765
+T55J5 684
+KK677 28
+KTJJT 220
+QQQJA 483
+2345J 1
+J2345 1
+JJJJJ 1
+AAAAJ 1";
+        let hands = parse_game::<JokersWild>(input)?;
+        for a in &hands {
+            for b in &hands {
+                let by_cards = a.hand_type().cmp(&b.hand_type()).then_with(|| {
+                    a.cards
+                        .0
+                        .iter()
+                        .map(JokersWild::card_rank)
+                        .cmp(b.cards.0.iter().map(JokersWild::card_rank))
+                });
+                assert_eq!(by_cards, a.cmp(b), "{:?} vs {:?}", a.cards, b.cards);
+            }
+        }
+        Ok(())
+    }
+
+    // tries every natural card for every wild card and keeps the best hand type
+    fn oracle_hand_type(cards: &[Card], is_wild: impl Fn(&Card) -> bool) -> HandType {
+        let wild = cards.iter().positions(is_wild).collect_vec();
+        (0..wild.len())
+            .map(|_| Card::NATURAL)
+            .multi_cartesian_product()
+            .map(|substitution| {
+                let mut played_as = cards.to_vec();
+                for (i, card) in wild.iter().zip(substitution) {
+                    played_as[*i] = card;
+                }
+                Standard::hand_type(&played_as, &[])
+            })
+            .max()
+            .unwrap_or_else(|| Standard::hand_type(cards, &[]))
+    }
+
+    #[test]
+    fn test_jokers_played_as() -> anyhow::Result<()> {
+        let played_as = |input| -> anyhow::Result<String> {
+            let (_, hand) = hand::<JokersWild>(input)?;
+            Ok(JokersWild::played_as(&hand.cards.0, &hand.suits)
+                .iter()
+                .join(""))
+        };
+        assert_eq!("KKQQK", played_as("KKQQJ 1")?);
+        assert_eq!("QKQQQ", played_as("QKQQJ 1")?);
+        assert_eq!("23455", played_as("2345J 1")?);
+        assert_eq!("AAAAA", played_as("JJJJJ 1")?);
+        assert_eq!(
+            HandType::FullHouse,
+            hand::<JokersWild>("KKQQJ 1")?.1.hand_type()
+        );
+        Ok(())
+    }
+
+    // a few wild cards at most, so the oracle stays quick
+    fn wild_hand() -> impl Strategy<Value = String> {
+        prop::collection::vec(
+            prop::sample::select(vec!['2', '3', 'T', 'Q', 'K', 'A', 'J', 'J']),
+            5,
+        )
+        .prop_filter("at most 3 wild cards", |cards| {
+            cards
+                .iter()
+                .filter(|card| matches!(card, 'J' | '2'))
+                .count()
+                <= 3
+        })
+        .prop_map(|cards| format!("{} 1", cards.into_iter().collect::<String>()))
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn test_jokers_wild_matches_oracle(input in wild_hand()) {
+            let (_, hand) = hand::<JokersWild>(&input).unwrap();
+            prop_assert_eq!(
+                oracle_hand_type(&hand.cards.0, |c| *c == Card::Joker),
+                hand.hand_type()
+            );
+            prop_assert_eq!(
+                hand.hand_type(),
+                Standard::hand_type(&JokersWild::played_as(&hand.cards.0, &hand.suits), &[])
+            );
+        }
+
+        #[test]
+        fn test_wild_sets_match_oracle(input in wild_hand()) {
+            let (_, hand) = hand::<Camel<JokersAndDeuces>>(&input).unwrap();
+            prop_assert_eq!(
+                oracle_hand_type(&hand.cards.0, JokersAndDeuces::is_wild),
+                hand.hand_type()
+            );
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use itertools::Itertools;
+use serde::Serialize;
+
+use crate::engine::{parse_game, Card, Hand, HandType, RuleSet, Suit};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RankedHand {
+    pub rank: u64,
+    pub cards: String,
+    pub played_as: String,
+    pub hand_type: HandType,
+    pub bid: u64,
+    pub winnings: u64,
+    // why the hand ranks above the one just below it, the weakest hand has nothing to beat
+    pub decided_by: Option<Decider>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Decider {
+    HandType,
+    // 1-based position in the order the rule set breaks ties in, which for Camel Cards is
+    // simply the order of the cards in the hand
+    Card(usize),
+    Tie,
+}
+
+pub fn ranking_report<R: RuleSet>(input: &str) -> anyhow::Result<Vec<RankedHand>> {
+    let mut hands = parse_game::<R>(input)?;
+    hands.sort();
+
+    Ok(hands
+        .iter()
+        .enumerate()
+        .map(|(i, hand)| {
+            let rank = i as u64 + 1;
+            RankedHand {
+                rank,
+                cards: format_cards(&hand.cards.0, &hand.suits),
+                played_as: format_cards(&R::played_as(&hand.cards.0, &hand.suits), &hand.suits),
+                hand_type: hand.hand_type(),
+                bid: hand.bid,
+                winnings: rank * hand.bid,
+                decided_by: i.checked_sub(1).map(|prev| decider(&hands[prev], hand)),
+            }
+        })
+        .collect())
+}
+
+fn format_cards(cards: &[Card], suits: &[Suit]) -> String {
+    match suits.is_empty() {
+        true => cards.iter().join(""),
+        false => cards
+            .iter()
+            .zip(suits)
+            .map(|(card, suit)| format!("{}{}", card, suit))
+            .join(""),
+    }
+}
+
+// the sort key holds the hand type in its top nibble and one card per nibble after that,
+// so the highest bit that differs says what settled it
+fn decider<R: RuleSet>(lower: &Hand<R>, higher: &Hand<R>) -> Decider {
+    let difference = lower.sort_key() ^ higher.sort_key();
+    if difference == 0 {
+        return Decider::Tie;
+    }
+    let nibble = (63 - difference.leading_zeros() as usize) / 4;
+    match nibble >= R::HAND_SIZE {
+        true => Decider::HandType,
+        false => Decider::Card(R::HAND_SIZE - nibble),
+    }
+}
+
+pub fn format_table(report: &[RankedHand]) -> String {
+    let header = [
+        "rank",
+        "cards",
+        "played as",
+        "type",
+        "bid",
+        "winnings",
+        "decided by",
+    ]
+    .map(String::from)
+    .to_vec();
+    let rows = report
+        .iter()
+        .map(|hand| {
+            vec![
+                hand.rank.to_string(),
+                hand.cards.clone(),
+                hand.played_as.clone(),
+                format!("{:?}", hand.hand_type),
+                hand.bid.to_string(),
+                hand.winnings.to_string(),
+                match hand.decided_by {
+                    None => String::new(),
+                    Some(Decider::HandType) => "type".to_string(),
+                    Some(Decider::Card(position)) => format!("card {}", position),
+                    Some(Decider::Tie) => "tie".to_string(),
+                },
+            ]
+        })
+        .collect_vec();
+
+    let widths = (0..header.len())
+        .map(|column| {
+            std::iter::once(&header)
+                .chain(&rows)
+                .map(|row| row[column].len())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect_vec();
+
+    std::iter::once(&header)
+        .chain(&rows)
+        .map(|row| {
+            row.iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                .join("  ")
+                .trim_end()
+                .to_string()
+        })
+        .join("\n")
+}
+
+pub fn format_json(report: &[RankedHand]) -> anyhow::Result<String> {
+    Ok(serde_json::to_string_pretty(report)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{engine::JokersWild, variants::Poker};
+
+    const GAME: &str = "32T3K 765
+T55J5 684
+KK677 28
+KTJJT 220
+QQQJA 483";
+
+    #[test]
+    fn test_ranking_report() -> anyhow::Result<()> {
+        let report = ranking_report::<JokersWild>(GAME)?;
+        assert_eq!(
+            vec!["32T3K", "KK677", "T55J5", "QQQJA", "KTJJT"],
+            report.iter().map(|h| h.cards.as_str()).collect_vec()
+        );
+        assert_eq!(
+            vec!["32T3K", "KK677", "T5555", "QQQQA", "KTTTT"],
+            report.iter().map(|h| h.played_as.as_str()).collect_vec()
+        );
+        assert_eq!(
+            vec![
+                None,
+                Some(Decider::HandType),
+                Some(Decider::HandType),
+                Some(Decider::Card(1)),
+                Some(Decider::Card(1)),
+            ],
+            report.iter().map(|h| h.decided_by).collect_vec()
+        );
+        assert_eq!(5905, report.iter().map(|h| h.winnings).sum::<u64>());
+        Ok(())
+    }
+
+    #[test]
+    fn test_decider_positions() -> anyhow::Result<()> {
+        let report = ranking_report::<JokersWild>("KK677 1\nKK676 2\nKK677 3")?;
+        assert_eq!(
+            vec![None, Some(Decider::Card(5)), Some(Decider::Tie)],
+            report.iter().map(|h| h.decided_by).collect_vec()
+        );
+
+        let report = ranking_report::<Poker>("2c2dKh3s4h 1\n2h2sKdQs4c 2")?;
+        assert_eq!(Some(Decider::Card(4)), report[1].decided_by);
+        assert_eq!("2h2sKdQs4c", report[1].cards);
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_table() -> anyhow::Result<()> {
+        let report = ranking_report::<JokersWild>("32T3K 765\nT55J5 684")?;
+        assert_eq!(
+            "rank  cards  played as  type        bid  winnings  decided by
+1     32T3K  32T3K      OnePair     765  765
+2     T55J5  T5555      FourOfKind  684  1368      type",
+            format_table(&report)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_json() -> anyhow::Result<()> {
+        let report = ranking_report::<JokersWild>("32T3K 765\nT55J5 684\nT55J5 1")?;
+        let json: serde_json::Value = serde_json::from_str(&format_json(&report)?)?;
+        assert_eq!("T55J5", json[1]["cards"]);
+        assert_eq!("T5555", json[1]["played_as"]);
+        assert_eq!("FourOfKind", json[1]["hand_type"]);
+        assert_eq!(1368, json[1]["winnings"]);
+        assert_eq!(serde_json::Value::Null, json[0]["decided_by"]);
+        assert_eq!("hand_type", json[1]["decided_by"]);
+        assert_eq!("tie", json[2]["decided_by"]);
+        Ok(())
+    }
+}
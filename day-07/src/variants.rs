@@ -0,0 +1,592 @@
+use std::{cmp::Reverse, marker::PhantomData};
+
+use itertools::Itertools;
+
+use crate::engine::{
+    card_counts, pack_sort_key, promote_wild_cards, Card, HandType, RuleSet, Suit,
+};
+
+// the cards that stand in for whichever card makes the best hand
+pub trait Wilds: std::fmt::Debug + Eq {
+    const CARDS: &'static [Card];
+
+    fn is_wild(card: &Card) -> bool {
+        Self::CARDS.contains(card)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NoWilds;
+
+impl Wilds for NoWilds {
+    const CARDS: &'static [Card] = &[];
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Jokers;
+
+impl Wilds for Jokers {
+    const CARDS: &'static [Card] = &[Card::Joker];
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Deuces;
+
+impl Wilds for Deuces {
+    const CARDS: &'static [Card] = &[Card::Two];
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JokersAndDeuces;
+
+impl Wilds for JokersAndDeuces {
+    const CARDS: &'static [Card] = &[Card::Joker, Card::Two];
+}
+
+// `J` is only a joker when jokers are wild, otherwise it is a jack
+fn wild_card<W: Wilds>(ch: char) -> anyhow::Result<Card> {
+    match ch {
+        'J' if W::CARDS.contains(&Card::Joker) => Ok(Card::Joker),
+        ch => Card::try_from(ch),
+    }
+}
+
+// Camel Cards with any set of wild cards and any number of cards in a hand
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Camel<W = NoWilds, const SIZE: usize = 5>(PhantomData<W>);
+
+impl<W: Wilds, const SIZE: usize> RuleSet for Camel<W, SIZE> {
+    const HAND_SIZE: usize = SIZE;
+
+    fn card(ch: char) -> anyhow::Result<Card> {
+        wild_card::<W>(ch)
+    }
+
+    fn card_rank(card: &Card) -> u8 {
+        *card as u8
+    }
+
+    fn hand_type(cards: &[Card], suits: &[Suit]) -> HandType {
+        let played_as = Self::played_as(cards, suits);
+        HandType::from_counts(card_counts(&played_as).into_values().collect_vec())
+    }
+
+    fn played_as(cards: &[Card], _suits: &[Suit]) -> Vec<Card> {
+        promote_wild_cards(cards, W::is_wild)
+    }
+}
+
+// classical poker ranking with straights and flushes, ties are broken by the biggest groups
+// first rather than card by card
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Poker<W = NoWilds, const SIZE: usize = 5>(PhantomData<W>);
+
+impl<W: Wilds, const SIZE: usize> RuleSet for Poker<W, SIZE> {
+    const HAND_SIZE: usize = SIZE;
+    const SUITED: bool = true;
+
+    fn card(ch: char) -> anyhow::Result<Card> {
+        wild_card::<W>(ch)
+    }
+
+    fn card_rank(card: &Card) -> u8 {
+        *card as u8
+    }
+
+    fn hand_type(cards: &[Card], suits: &[Suit]) -> HandType {
+        best_poker_hand::<W>(cards, suits).hand_type
+    }
+
+    fn played_as(cards: &[Card], suits: &[Suit]) -> Vec<Card> {
+        let mut substitution = best_poker_hand::<W>(cards, suits).substitution.into_iter();
+        cards
+            .iter()
+            .map(|c| match W::is_wild(c) {
+                true => substitution.next().expect("A rank for every wild card"),
+                false => *c,
+            })
+            .collect()
+    }
+
+    // the best hand is a brute force search, so the type and key come out of the same one
+    fn evaluate(cards: &[Card], suits: &[Suit]) -> (HandType, u64) {
+        let best = best_poker_hand::<W>(cards, suits);
+        (best.hand_type, best.sort_key)
+    }
+}
+
+struct PokerHand {
+    hand_type: HandType,
+    sort_key: u64,
+    // what each wild card became, in the order they appear in the hand
+    substitution: Vec<Card>,
+}
+
+// wild cards either group up with the other cards, once to make the biggest counted hand
+// type and once for the highest cards of a flush, or fill the gaps in a straight. they all
+// take the suit of the other cards whenever that could make a flush. that leaves a dozen or
+// so hands to try however many wild cards there are
+fn best_poker_hand<W: Wilds>(cards: &[Card], suits: &[Suit]) -> PokerHand {
+    let (natural, wild): (Vec<_>, Vec<_>) = cards
+        .iter()
+        .zip(suits)
+        .partition(|(card, _)| !W::is_wild(card));
+
+    if natural.is_empty() {
+        let ranks = vec![Card::Ace as u8; cards.len()];
+        return PokerHand {
+            hand_type: HandType::FiveOfKind,
+            sort_key: pack_sort_key(HandType::FiveOfKind, ranks.into_iter()),
+            substitution: vec![Card::Ace; cards.len()],
+        };
+    }
+
+    let natural_ranks = natural.iter().map(|(c, _)| **c as u8).collect_vec();
+    let natural_suits = natural.iter().map(|(_, s)| **s).collect_vec();
+    let wild_suit = match natural_suits.iter().all_equal() {
+        true => natural_suits[0],
+        false => Suit::Spades,
+    };
+    let suits = natural_suits
+        .into_iter()
+        .chain(std::iter::repeat_n(wild_suit, wild.len()))
+        .collect_vec();
+
+    // all the wild cards join the biggest group until it makes five of a kind
+    let biggest_group = natural_ranks
+        .iter()
+        .counts()
+        .into_values()
+        .max()
+        .unwrap_or(0);
+    let groupings = [
+        best_grouping(
+            &natural_ranks,
+            wild.len(),
+            (biggest_group + wild.len()).min(5),
+        ),
+        best_grouping(&natural_ranks, wild.len(), 0),
+    ];
+    let straights = straight_windows(cards.len()).filter_map(|window| {
+        match natural_ranks.iter().all_unique() && natural_ranks.iter().all(|r| window.contains(r))
+        {
+            true => Some(
+                window
+                    .into_iter()
+                    .filter(|r| !natural_ranks.contains(r))
+                    .collect_vec(),
+            ),
+            false => None,
+        }
+    });
+
+    groupings
+        .into_iter()
+        .chain(straights)
+        .unique()
+        .map(|substitution| {
+            let ranks = natural_ranks
+                .iter()
+                .chain(&substitution)
+                .copied()
+                .collect_vec();
+            let (hand_type, tie_break) = poker_hand(&ranks, &suits);
+            PokerHand {
+                hand_type,
+                sort_key: pack_sort_key(hand_type, tie_break.into_iter()),
+                substitution: substitution.into_iter().flat_map(Card::from_rank).collect(),
+            }
+        })
+        .max_by_key(|hand| hand.sort_key)
+        .expect("There is always at least one substitution")
+}
+
+// the ranks the wild cards turn into for the highest tie-break once the hand is grouped by
+// how many there are of each rank, with at least `min_top` cards in the biggest group.
+// the grouped ranks are picked one at a time, each time the highest rank that still leaves
+// somewhere for the rest of the wild cards to go
+fn best_grouping(natural_ranks: &[u8], wilds: usize, min_top: usize) -> Vec<u8> {
+    let mut natural = [0; Card::Ace as usize + 1];
+    for rank in natural_ranks {
+        natural[*rank as usize] += 1;
+    }
+
+    let mut groups: Vec<(u8, usize)> = Vec::new();
+    for _ in 0..natural_ranks.len() + wilds {
+        let open = groups.last().map(|(rank, _)| *rank);
+        let extend = |groups: &mut Vec<(u8, usize)>, rank: u8| match groups.last_mut() {
+            Some((last, count)) if *last == rank => *count += 1,
+            _ => groups.push((rank, 1)),
+        };
+        let next = (Card::Two as u8..=Card::Ace as u8)
+            .rev()
+            .filter(|rank| Some(*rank) == open || groups.iter().all(|(r, _)| r != rank))
+            .find(|rank| {
+                let mut groups = groups.clone();
+                extend(&mut groups, *rank);
+                can_complete(&groups, &natural, wilds, min_top)
+            })
+            .expect("The greedy choice always leaves a way to finish the hand");
+        extend(&mut groups, next);
+    }
+
+    groups
+        .into_iter()
+        .flat_map(|(rank, count)| std::iter::repeat_n(rank, count - natural[rank as usize]))
+        .collect()
+}
+
+// whether the groups so far can be finished off into a whole hand. the last group can still
+// grow, every rank without a group yet has to sort after it, and the wild cards have to run
+// out exactly
+fn can_complete(groups: &[(u8, usize)], natural: &[usize], wilds: usize, min_top: usize) -> bool {
+    let Some((&(open_rank, open_count), closed)) = groups.split_last() else {
+        return false;
+    };
+    let ordered = |(rank, count): (u8, usize), (next_rank, next_count): (u8, usize)| {
+        (count, rank) > (next_count, next_rank)
+    };
+    if closed.iter().tuple_windows().any(|(a, b)| !ordered(*a, *b)) {
+        return false;
+    }
+    let Some(closed_wilds) = closed
+        .iter()
+        .map(|(rank, count)| count.checked_sub(natural[*rank as usize]))
+        .sum::<Option<usize>>()
+    else {
+        return false;
+    };
+
+    let size = natural.iter().sum::<usize>() + wilds;
+    (open_count.max(natural[open_rank as usize])..=size).any(|total| {
+        let used = closed_wilds + total - natural[open_rank as usize];
+        let top = closed.first().map_or(total, |(_, count)| *count);
+        let room = (Card::Two as u8..=Card::Ace as u8)
+            .filter(|rank| groups.iter().all(|(r, _)| r != rank))
+            .map(|rank| match rank < open_rank {
+                true => total.checked_sub(natural[rank as usize]),
+                false => (total - 1).checked_sub(natural[rank as usize]),
+            })
+            .sum::<Option<usize>>();
+
+        closed
+            .last()
+            .is_none_or(|last| ordered(*last, (open_rank, total)))
+            && top >= min_top
+            && used <= wilds
+            && room.is_some_and(|room| wilds - used <= room)
+    })
+}
+
+// every run of `size` ranks a straight can be made of, aces low as well as high
+fn straight_windows(size: usize) -> impl Iterator<Item = Vec<u8>> {
+    let (two, ace) = (Card::Two as u8, Card::Ace as u8);
+    let aces_low = match size {
+        2..=13 => Some(
+            std::iter::once(ace)
+                .chain(two..two + size as u8 - 1)
+                .collect(),
+        ),
+        _ => None,
+    };
+    (two as usize + size.max(1) - 1..=ace as usize)
+        .map(move |top| (top + 1 - size.max(1)..=top).map(|r| r as u8).collect())
+        .chain(aces_low)
+}
+
+// the type of a hand without wild cards, along with its ranks in tie-break order
+fn poker_hand(ranks: &[u8], suits: &[Suit]) -> (HandType, Vec<u8>) {
+    let counts = ranks.iter().counts();
+    let mut grouped = ranks.to_vec();
+    grouped.sort_by_key(|r| (Reverse(counts[r]), Reverse(*r)));
+    let counted_type =
+        HandType::from_counts(counts.values().map(|count| *count as u8).collect_vec());
+
+    let flush = suits.iter().all_equal();
+    let straight = straight_ranks(ranks);
+    let made_type = match (&straight, flush) {
+        (Some(_), true) => HandType::StraightFlush,
+        (Some(_), false) => HandType::Straight,
+        (None, true) => HandType::Flush,
+        (None, false) => HandType::HighCard,
+    };
+
+    match (made_type > counted_type, straight) {
+        (true, Some(straight)) => (made_type, straight),
+        (true, None) => (made_type, grouped),
+        (false, _) => (counted_type, grouped),
+    }
+}
+
+// the ranks of a straight from the top down, aces count as 1 when they start one
+fn straight_ranks(ranks: &[u8]) -> Option<Vec<u8>> {
+    let ace = Card::Ace as u8;
+    let mut sorted = ranks.to_vec();
+    sorted.sort_unstable_by(|a, b| b.cmp(a));
+    if sorted.iter().tuple_windows().any(|(a, b)| a == b) {
+        return None;
+    }
+
+    let is_run = |run: &[u8]| run.iter().tuple_windows().all(|(a, b)| *a == b + 1);
+    if is_run(&sorted) {
+        return Some(sorted);
+    }
+
+    let mut aces_low = sorted
+        .iter()
+        .map(|r| if *r == ace { 1 } else { *r })
+        .collect_vec();
+    aces_low.sort_unstable_by(|a, b| b.cmp(a));
+    match is_run(&aces_low) {
+        true => Some(aces_low),
+        false => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::engine::{hand, parse_game, total_winnings, Hand, HandCards};
+
+    fn hand_type<R: RuleSet>(input: &str) -> HandType {
+        hand::<R>(input).unwrap().1.hand_type()
+    }
+
+    #[test]
+    fn test_camel_wild_sets() {
+        assert_eq!(
+            HandType::ThreeOfKind,
+            hand_type::<Camel<JokersAndDeuces>>("2J345 1")
+        );
+        assert_eq!(HandType::OnePair, hand_type::<Camel<Jokers>>("2J345 1"));
+        assert_eq!(HandType::OnePair, hand_type::<Camel<Deuces>>("2J345 1"));
+        assert_eq!(
+            HandType::FiveOfKind,
+            hand_type::<Camel<JokersAndDeuces>>("2JJ22 1")
+        );
+    }
+
+    #[test]
+    fn test_camel_hand_sizes() -> anyhow::Result<()> {
+        assert_eq!(HandType::OnePair, hand_type::<Camel<NoWilds, 3>>("KKQ 1"));
+        assert_eq!(
+            HandType::FiveOfKind,
+            hand_type::<Camel<NoWilds, 7>>("KKKKKKQ 1")
+        );
+        assert_eq!(
+            HandType::FullHouse,
+            hand_type::<Camel<NoWilds, 7>>("KKKQQQ2 1")
+        );
+        assert!(parse_game::<Camel<NoWilds, 3>>("KKQQ 1").is_err());
+
+        // the biggest hand the sort key has room for still ranks by type first
+        let game = parse_game::<Camel<NoWilds, 15>>("222222222222222 1\nAKQJT98765432AK 2")?;
+        assert_eq!(HandType::FiveOfKind, game[0].hand_type());
+        assert_eq!(HandType::TwoPair, game[1].hand_type());
+        assert!(game[0] > game[1]);
+        assert!(parse_game::<Camel<NoWilds, 3>>("KK 1").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_poker_hand_types() {
+        for (input, expected) in [
+            ("AhKhQhJhTh 1", HandType::StraightFlush),
+            ("Ah2c3d4s5h 1", HandType::Straight),
+            ("2h7h9hJhKh 1", HandType::Flush),
+            ("AhAcKdKsKh 1", HandType::FullHouse),
+            ("KhKcKdKsKh 1", HandType::FiveOfKind),
+            ("QhKc2d3s4h 1", HandType::HighCard),
+        ] {
+            assert_eq!(expected, hand_type::<Poker>(input), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_poker_tie_breaks() -> anyhow::Result<()> {
+        let (_, wheel) = hand::<Poker>("Ah2c3d4s5h 1")?;
+        let (_, six_high) = hand::<Poker>("2c3d4s5h6h 1")?;
+        assert!(wheel < six_high);
+
+        let (_, kings) = hand::<Poker>("2dKhKc3s4h 1")?;
+        let (_, queens) = hand::<Poker>("AdQhQcKsJh 1")?;
+        assert!(kings > queens);
+
+        let (_, flush) = hand::<Poker>("2h7h9hJhKh 1")?;
+        assert!(flush < hand::<Poker>("AhAcKdKsKh 1")?.1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_poker_needs_suits() -> anyhow::Result<()> {
+        use Card::*;
+        let cards = || HandCards(vec![Two, Three, Four, Six, Nine]);
+        assert!(Hand::<Poker>::with_suits(cards(), Vec::new(), 1).is_err());
+        assert!(Hand::<Poker>::with_suits(cards(), vec![Suit::Clubs; 4], 1).is_err());
+
+        let hand = Hand::<Poker>::with_suits(cards(), vec![Suit::Clubs; 5], 1)?;
+        assert_eq!(HandType::Flush, hand.hand_type());
+        assert!(Hand::<Camel>::with_suits(cards(), vec![Suit::Clubs; 5], 1).is_err());
+        assert_eq!(
+            HandType::HighCard,
+            Hand::<Camel>::with_suits(cards(), Vec::new(), 1)?.hand_type()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_poker_wilds() {
+        assert_eq!(
+            HandType::StraightFlush,
+            hand_type::<Poker<Deuces>>("2cAhKhQhJh 1")
+        );
+        assert_eq!(
+            HandType::FiveOfKind,
+            hand_type::<Poker<Deuces>>("2c2dKhKsKd 1")
+        );
+        assert_eq!(
+            HandType::FiveOfKind,
+            hand_type::<Poker<Deuces>>("2c2d2h2s2c 1")
+        );
+        assert_eq!(
+            HandType::Straight,
+            hand_type::<Poker<Deuces>>("2c3d4s6h7h 1")
+        );
+        assert_eq!(HandType::Flush, hand_type::<Poker<Deuces>>("2c3d8d9dQd 1"));
+    }
+
+    #[test]
+    fn test_poker_many_wilds() {
+        assert_eq!(
+            HandType::FiveOfKind,
+            hand_type::<Poker<Deuces, 15>>("2c2c2c2c2c2c2c2c2c2c2c2c2c2c3h 1")
+        );
+        assert_eq!(
+            HandType::FiveOfKind,
+            hand_type::<Poker<Deuces, 13>>("2c2c2c2c2c2c2c2c2c2c2c2c5d 1")
+        );
+        assert_eq!(
+            HandType::StraightFlush,
+            hand_type::<Poker<Deuces, 6>>("2c3d4d5d7d8d 1")
+        );
+    }
+
+    // tries every rank for every wild card
+    fn brute_force_poker_hand<W: Wilds>(cards: &[Card], suits: &[Suit]) -> (HandType, u64) {
+        let (natural, wild): (Vec<_>, Vec<_>) = cards
+            .iter()
+            .zip(suits)
+            .partition(|(card, _)| !W::is_wild(card));
+        if natural.is_empty() {
+            let ranks = vec![Card::Ace as u8; cards.len()];
+            return (
+                HandType::FiveOfKind,
+                pack_sort_key(HandType::FiveOfKind, ranks.into_iter()),
+            );
+        }
+
+        let natural_suits = natural.iter().map(|(_, s)| **s).collect_vec();
+        let wild_suit = match natural_suits.iter().all_equal() {
+            true => natural_suits[0],
+            false => Suit::Spades,
+        };
+        let suits = natural_suits
+            .into_iter()
+            .chain(std::iter::repeat_n(wild_suit, wild.len()))
+            .collect_vec();
+
+        let substitutions = match wild.len() {
+            0 => vec![Vec::new()],
+            n => (0..n)
+                .map(|_| Card::Two as u8..=Card::Ace as u8)
+                .multi_cartesian_product()
+                .collect_vec(),
+        };
+        substitutions
+            .into_iter()
+            .map(|substitution| {
+                let ranks = natural
+                    .iter()
+                    .map(|(c, _)| **c as u8)
+                    .chain(substitution)
+                    .collect_vec();
+                let (hand_type, tie_break) = poker_hand(&ranks, &suits);
+                (hand_type, pack_sort_key(hand_type, tie_break.into_iter()))
+            })
+            .max_by_key(|(_, sort_key)| *sort_key)
+            .expect("There is always at least one substitution")
+    }
+
+    // a few wild cards at most, so the brute force stays quick
+    fn poker_hand_input(size: usize) -> impl Strategy<Value = String> {
+        prop::collection::vec(
+            (
+                prop::sample::select(vec!['2', '2', '3', '4', '5', '9', 'T', 'J', 'Q', 'K', 'A']),
+                prop::sample::select(vec!['h', 'h', 's']),
+            ),
+            size,
+        )
+        .prop_filter("at most 3 wild cards", |cards| {
+            cards.iter().filter(|(card, _)| *card == '2').count() <= 3
+        })
+        .prop_map(|cards| {
+            let cards = cards
+                .into_iter()
+                .map(|(card, suit)| format!("{}{}", card, suit))
+                .join("");
+            format!("{} 1", cards)
+        })
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn test_poker_matches_brute_force(input in poker_hand_input(5)) {
+            let (_, hand) = hand::<Poker<Deuces>>(&input).unwrap();
+            prop_assert_eq!(
+                brute_force_poker_hand::<Deuces>(&hand.cards.0, &hand.suits),
+                (hand.hand_type(), hand.sort_key())
+            );
+        }
+
+        #[test]
+        fn test_bigger_poker_hands_match_brute_force(input in poker_hand_input(7)) {
+            let (_, hand) = hand::<Poker<Deuces, 7>>(&input).unwrap();
+            prop_assert_eq!(
+                brute_force_poker_hand::<Deuces>(&hand.cards.0, &hand.suits),
+                (hand.hand_type(), hand.sort_key())
+            );
+        }
+
+        #[test]
+        fn test_big_poker_hands_match_brute_force(input in poker_hand_input(11)) {
+            let (_, hand) = hand::<Poker<Deuces, 11>>(&input).unwrap();
+            prop_assert_eq!(
+                brute_force_poker_hand::<Deuces>(&hand.cards.0, &hand.suits),
+                (hand.hand_type(), hand.sort_key())
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_poker_hands() {
+        let e = parse_game::<Poker>("AhKxQhJhTh 1").unwrap_err().to_string();
+        assert!(e.ends_with("Invalid suit: 'x'"), "{}", e);
+        let e = parse_game::<Poker>("AhKhQhJhT 1").unwrap_err().to_string();
+        assert!(e.ends_with("Expected a suit after the last card"), "{}", e);
+        let e = parse_game::<Poker>("AhKhQhJh 1").unwrap_err().to_string();
+        assert!(e.ends_with("Expected 5 cards but found 4"), "{}", e);
+    }
+
+    #[test]
+    fn test_poker_solve() -> anyhow::Result<()> {
+        let game = "AhKhQhJhTh 1
+2h7h9hJhKh 10
+Ah2c3d4s5h 100";
+        assert_eq!(3 + 2 * 10 + 100, total_winnings::<Poker>(game)?);
+        Ok(())
+    }
+}
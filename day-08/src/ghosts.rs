@@ -0,0 +1,261 @@
+use std::collections::HashMap;
+
+use anyhow::anyhow;
+use itertools::Itertools;
+use num::Integer;
+
+use crate::part1::{Direction, Map};
+
+// everywhere one ghost stands on a Z node, the walk is a fixed lead in followed by a loop
+// that repeats every `period` steps forever
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GhostPath {
+    // Z hits before the ghost enters its loop, these only happen once
+    pub lead_in_hits: Vec<u64>,
+    // step at which the ghost first stands in the state it keeps coming back to
+    pub cycle_start: u64,
+    pub period: u64,
+    // Z hits during the first time round the loop, each repeats every `period` steps
+    pub cycle_hits: Vec<u64>,
+}
+
+impl GhostPath {
+    // a ghost is only back where it was once both its node and its place in the instructions
+    // repeat, so the walk is followed until a (node, instruction index) pair comes up again
+    pub fn walk(start: &str, directions: &[Direction], map: &Map) -> anyhow::Result<GhostPath> {
+        let mut seen: HashMap<(&str, usize), u64> = HashMap::new();
+        let mut hits = vec![];
+        let mut current = start;
+        let mut step = 0;
+
+        let cycle_start = loop {
+            let instruction = step as usize % directions.len();
+            if let Some(&first_seen) = seen.get(&(current, instruction)) {
+                break first_seen;
+            }
+            seen.insert((current, instruction), step);
+            if current.ends_with('Z') {
+                hits.push(step);
+            }
+
+            let entry = map
+                .get(current)
+                .ok_or_else(|| anyhow!("Dead end at {}", current))?;
+            current = match directions[instruction] {
+                Direction::Left => &entry.0,
+                Direction::Right => &entry.1,
+            };
+            step += 1;
+        };
+
+        let (lead_in_hits, cycle_hits) = hits.into_iter().partition(|hit| *hit < cycle_start);
+        Ok(GhostPath {
+            lead_in_hits,
+            cycle_start,
+            period: step - cycle_start,
+            cycle_hits,
+        })
+    }
+
+    pub fn hits_at(&self, step: u64) -> bool {
+        match step.checked_sub(self.cycle_start) {
+            Some(into_cycle) => self
+                .cycle_hits
+                .contains(&(self.cycle_start + into_cycle % self.period)),
+            None => self.lead_in_hits.contains(&step),
+        }
+    }
+
+    // every Z hit below `limit` in step order
+    fn hits_before(&self, limit: u64) -> Vec<u64> {
+        self.lead_in_hits
+            .iter()
+            .copied()
+            .chain(
+                self.cycle_hits
+                    .iter()
+                    .flat_map(|hit| (*hit..limit).step_by(self.period as usize)),
+            )
+            .filter(|hit| *hit < limit)
+            .sorted()
+            .collect()
+    }
+}
+
+// the first step where every ghost stands on a Z node at once, `None` when that never happens
+pub fn first_common_hit(paths: &[GhostPath]) -> Option<u64> {
+    let settled = paths.iter().map(|path| path.cycle_start).max()?;
+
+    // until the last ghost reaches its loop, the hits can simply be checked one by one
+    if let Some(step) = paths[0]
+        .hits_before(settled)
+        .into_iter()
+        .find(|step| paths.iter().all(|path| path.hits_at(*step)))
+    {
+        return Some(step);
+    }
+
+    // after that each ghost is on a Z node exactly when the step lands on one of its cycle
+    // hits modulo its period, so every choice of one cycle hit per ghost is a CRT system
+    paths
+        .iter()
+        .map(|path| {
+            path.cycle_hits
+                .iter()
+                .map(|hit| ((hit % path.period) as i128, path.period as i128))
+                .collect_vec()
+        })
+        .multi_cartesian_product()
+        .filter_map(|congruences| {
+            let (remainder, modulus) = congruences.into_iter().try_fold((0, 1), crt)?;
+            let behind = (settled as i128 - remainder).max(0);
+            u64::try_from(remainder + Integer::div_ceil(&behind, &modulus) * modulus).ok()
+        })
+        .min()
+}
+
+// merges x = r1 (mod m1) and x = r2 (mod m2) into one congruence, the moduli do not need to
+// be coprime but then the remainders have to agree modulo their gcd
+fn crt((r1, m1): (i128, i128), (r2, m2): (i128, i128)) -> Option<(i128, i128)> {
+    let gcd = m1.extended_gcd(&m2);
+    if (r2 - r1) % gcd.gcd != 0 {
+        return None;
+    }
+    let step = m2 / gcd.gcd;
+    let k = ((r2 - r1) / gcd.gcd % step * gcd.x.rem_euclid(step)).rem_euclid(step);
+    let modulus = m1 * step;
+    Some(((r1 + m1 * k).rem_euclid(modulus), modulus))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::part1::parse_map;
+
+    // moves every ghost together until they all stand on a Z node
+    fn simulate(input: &str, limit: u64) -> anyhow::Result<Option<u64>> {
+        let (directions, map) = parse_map(input)?;
+        let mut ghosts = map.keys().filter(|v| v.ends_with('A')).collect_vec();
+        for step in 0..limit {
+            if ghosts.iter().all(|v| v.ends_with('Z')) {
+                return Ok(Some(step));
+            }
+            let direction = &directions[step as usize % directions.len()];
+            for ghost in ghosts.iter_mut() {
+                let entry = &map[*ghost];
+                *ghost = match direction {
+                    Direction::Left => &entry.0,
+                    Direction::Right => &entry.1,
+                };
+            }
+        }
+        Ok(None)
+    }
+
+    fn solve(input: &str) -> anyhow::Result<Option<u64>> {
+        let (directions, map) = parse_map(input)?;
+        let paths = map
+            .keys()
+            .filter(|v| v.ends_with('A'))
+            .map(|start| GhostPath::walk(start, &directions, &map))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(first_common_hit(&paths))
+    }
+
+    #[test]
+    fn test_walk() -> anyhow::Result<()> {
+        let (directions, map) = parse_map(
+            "LR
+
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+XXX = (XXX, XXX)",
+        )?;
+        assert_eq!(
+            GhostPath {
+                lead_in_hits: vec![],
+                cycle_start: 1,
+                period: 2,
+                cycle_hits: vec![2],
+            },
+            GhostPath::walk("11A", &directions, &map)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_offset_cycles() -> anyhow::Result<()> {
+        // the first ghost hits Z at 2, 5, 8, ... and the second at 3, 5, 7, ...
+        // so the first hits alone would suggest lcm(2, 3) = 6
+        let input = "L
+
+1AA = (1BB, 1BB)
+1BB = (1ZZ, 1ZZ)
+1ZZ = (1CC, 1CC)
+1CC = (1DD, 1DD)
+1DD = (1ZZ, 1ZZ)
+2AA = (2XX, 2XX)
+2XX = (2YY, 2YY)
+2YY = (2ZZ, 2ZZ)
+2ZZ = (2YY, 2YY)";
+        assert_eq!(Some(5), solve(input)?);
+        assert_eq!(simulate(input, 100)?, solve(input)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_lead_in_hits() -> anyhow::Result<()> {
+        // the first ghost passes a Z node once on the way into its loop, the second is only
+        // on a Z node every other step
+        let input = "L
+
+1AA = (1ZZ, 1ZZ)
+1ZZ = (1BB, 1BB)
+1BB = (1CC, 1CC)
+1CC = (1BB, 1BB)
+2AA = (2ZZ, 2ZZ)
+2ZZ = (2BB, 2BB)
+2BB = (2ZZ, 2ZZ)";
+        assert_eq!(Some(1), solve(input)?);
+
+        // several Z nodes in one loop, and loops with periods that share a factor
+        let input = "L
+
+1AA = (1XZ, 1XZ)
+1XZ = (1BB, 1BB)
+1BB = (1YZ, 1YZ)
+1YZ = (1CC, 1CC)
+1CC = (1DD, 1DD)
+1DD = (1EE, 1EE)
+1EE = (1XZ, 1XZ)
+2AA = (2BB, 2BB)
+2BB = (2CC, 2CC)
+2CC = (2ZZ, 2ZZ)
+2ZZ = (2BB, 2BB)";
+        assert_eq!(Some(3), solve(input)?);
+        assert_eq!(simulate(input, 100)?, solve(input)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_never_meet() -> anyhow::Result<()> {
+        // both ghosts loop every 2 steps but on opposite steps
+        let input = "L
+
+1AA = (1ZZ, 1ZZ)
+1ZZ = (1AA, 1AA)
+2AA = (2BB, 2BB)
+2BB = (2ZZ, 2ZZ)
+2ZZ = (2BB, 2BB)";
+        assert_eq!(None, solve(input)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(Some((5, 6)), crt((2, 3), (1, 2)));
+        assert_eq!(Some((10, 12)), crt((2, 4), (4, 6)));
+        assert_eq!(None, crt((1, 4), (2, 6)));
+    }
+}
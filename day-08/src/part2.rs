@@ -1,42 +1,27 @@
-use num::integer::lcm;
+use anyhow::anyhow;
 
-use itertools::Itertools;
-
-use crate::part1::{parse_map, Direction};
+use crate::{
+    ghosts::{first_common_hit, GhostPath},
+    part1::parse_map,
+};
 
 #[tracing::instrument(skip_all)]
 pub fn solve(input: &str) -> anyhow::Result<String> {
     let (directions, map) = parse_map(input)?;
 
-    let mut directions_it = directions.iter().cycle();
-
-    let start = map.keys().filter(|v| v.ends_with('A')).collect_vec();
-    
-    // find each individual path to a Z-suffix
-    let mut path_steps = start.iter().map(|v| {
-        let mut current = *v;
-        let mut step_count = 0;
-        while !current.ends_with('Z') {
-            step_count += 1;
-            let entry = map.get(current).expect("Dead end");
-            let direction = directions_it.next().unwrap();
-            current = match direction {
-                Direction::Left => &entry.0,
-                Direction::Right => &entry.1,
-            }
-        }
-        step_count
-    });
-
-    let init_lcm: u64 = lcm(path_steps.next().unwrap(), path_steps.next().unwrap());
-
-    // each path is a repeating cycle, find the common multiple to find when they'll sync
-    // thanks to this https://www.reddit.com/r/adventofcode/comments/18did3d/2023_day_8_part_1_my_input_maze_plotted_using/
-    let lcm = path_steps.fold(init_lcm, |acc, v| {
-        lcm(acc, v)
-    });
+    // every ghost starts at the first instruction and follows its own path until it loops
+    let paths = map
+        .keys()
+        .filter(|v| v.ends_with('A'))
+        .map(|start| GhostPath::walk(start, &directions, &map))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    if paths.is_empty() {
+        return Err(anyhow!("No starting nodes ending in A"));
+    }
 
-    Ok(lcm.to_string())
+    first_common_hit(&paths)
+        .map(|step| step.to_string())
+        .ok_or_else(|| anyhow!("The ghosts never all stand on a Z node at once"))
 }
 
 #[cfg(test)]